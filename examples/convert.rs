@@ -0,0 +1,19 @@
+use std::fs;
+use chipgame::core::{dto, leveltext};
+
+fn main() {
+	let mut args = std::env::args().skip(1);
+	let (Some(in_path), Some(out_path)) = (args.next(), args.next()) else {
+		panic!("Usage: cargo run --example convert <in> <out>\nWrites JSON if the output ends in .json, otherwise the text level format.");
+	};
+
+	let ld = dto::LevelDto::parse(&fs::read_to_string(&in_path).unwrap()).unwrap();
+
+	let out = if out_path.ends_with(".json") {
+		serde_json::to_string(&ld).unwrap()
+	}
+	else {
+		leveltext::format(&ld).unwrap()
+	};
+	fs::write(&out_path, out).unwrap();
+}
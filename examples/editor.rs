@@ -1,5 +1,7 @@
 use std::{fs, thread, time};
 
+mod gamepad;
+
 fn main() {
 	let Some(file_path) = std::env::args_os().nth(1) else {
 		panic!("Usage: cargo run --example editor <level>");
@@ -23,26 +25,55 @@ fn main() {
 	// Create the graphics context
 	let mut g = shade::gl::GlGraphics::new();
 
-	// Load the texture
-	let tileset = shade::png::load(&mut g, Some("scene tiles"), "data/Color_Tileset.png", &shade::png::TextureProps {
-		filter_min: shade::TextureFilter::Linear,
-		filter_mag: shade::TextureFilter::Linear,
+	let data = fs::read_to_string("data/data.json").unwrap();
+
+	// Load the tilesets
+	let mut skins = Vec::new();
+	for atlas in chipgame::visual::SpriteAtlas::parse_all(&data).unwrap() {
+		let path = format!("data/{}", atlas.texture);
+		skins.push(chipgame::visual::Skin::load(&mut g, atlas, &path).unwrap());
+	}
+
+	// Load the font, its pixels are drawn without filtering
+	let font_atlas = chipgame::visual::FontAtlas::parse_all(&data).unwrap().swap_remove(0);
+	let font_texture = shade::png::load(&mut g, Some(font_atlas.name.as_str()), &format!("data/{}", font_atlas.texture), &shade::png::TextureProps {
+		filter_min: shade::TextureFilter::Nearest,
+		filter_mag: shade::TextureFilter::Nearest,
 		wrap_u: shade::TextureWrap::ClampEdge,
 		wrap_v: shade::TextureWrap::ClampEdge,
-	}, Some(&mut shade::png::gutter(32, 32, 4))).unwrap();
-	let tex_info = g.texture2d_get_info(tileset).unwrap();
+	}, None).unwrap();
+	let tex_info = g.texture2d_get_info(font_texture).unwrap();
+	let font = chipgame::visual::Font::new(font_atlas, font_texture, [tex_info.width, tex_info.height].into()).unwrap();
 
-	// Create the shader
-	let shader = g.shader_create(None).unwrap();
-	if let Err(_) = g.shader_compile(shader, include_str!("../data/standard.vs.glsl"), include_str!("../data/standard.fs.glsl")) {
-		panic!("Failed to compile shader: {}", g.shader_compile_log(shader).unwrap());
-	}
+	// Load the entity meshes
+	let meshes = chipgame::visual::EntityMesh::load_all("data", &data).unwrap();
 
 	let mut past_now = time::Instant::now();
+	let mut frame_time = past_now;
 
 	let mut editor = chipgame::editor::EditorGame::default();
 	let mut input = chipgame::editor::EditorInput::default();
-	editor.load_level(&fs::read_to_string(&file_path).unwrap());
+	// Keyboard and gamepad input is mapped to the game through the bindings file while playtesting
+	let mut input_map = chipgame::bindings::InputMap::new(chipgame::bindings::Bindings::parse(&fs::read_to_string("data/bindings.json").unwrap()).unwrap());
+	let mut gilrs = gilrs::Gilrs::new().ok();
+	let mut resources = chipgame::visual::Resources::default();
+	resources.load_shader(&mut g, &fs::read_to_string("data/standard.vs.glsl").unwrap(), &fs::read_to_string("data/standard.fs.glsl").unwrap()).unwrap();
+	for skin in skins {
+		resources.add_skin(skin);
+	}
+	resources.set_font(&font);
+	for mesh in meshes {
+		resources.set_mesh(mesh);
+	}
+	// Changes to the shaders, the tileset and the data file are reloaded while running
+	resources.watch("data");
+	editor.init(resources);
+	editor.load_data(&data).unwrap();
+	editor.load_level(&fs::read_to_string(&file_path).unwrap()).unwrap();
+
+	let mut modifiers = winit::event::ModifiersState::empty();
+	// Dragging with the middle mouse button pans the camera
+	let mut panning = false;
 
 	// Main loop
 	let mut quit = false;
@@ -65,6 +96,12 @@ fn main() {
 				winit::event::Event::WindowEvent { event: winit::event::WindowEvent::CloseRequested, .. } => {
 					quit = true;
 				}
+				winit::event::Event::WindowEvent { event: winit::event::WindowEvent::Focused(false), .. } => {
+					input_map.release_all();
+				}
+				winit::event::Event::WindowEvent { event: winit::event::WindowEvent::ModifiersChanged(new_modifiers), .. } => {
+					modifiers = new_modifiers;
+				}
 				winit::event::Event::WindowEvent {
 					event: winit::event::WindowEvent::KeyboardInput {
 						input: winit::event::KeyboardInput {
@@ -76,54 +113,113 @@ fn main() {
 					},
 					..
 				} => {
+					// Keys are bound to game actions by the names of their key codes
+					if let Some(keycode) = virtual_keycode {
+						input_map.key(&format!("{:?}", keycode), is_pressed(state));
+					}
 					match virtual_keycode {
+						// While typing a property Enter or Escape finishes it, other keys only type
+						Some(winit::event::VirtualKeyCode::Return | winit::event::VirtualKeyCode::Escape) if is_pressed(state) && editor.text_field().is_some() => editor.end_text(),
+						_ if editor.text_field().is_some() => (),
+						// P starts playing the level and returns to editing
+						Some(winit::event::VirtualKeyCode::P) if is_pressed(state) => editor.toggle_playtest(),
+						// O switches between the perspective and top-down view, +/- zooms
+						Some(winit::event::VirtualKeyCode::O) if is_pressed(state) => editor.camera_mut().toggle_projection(),
+						Some(winit::event::VirtualKeyCode::Equals) if is_pressed(state) => editor.camera_mut().zoom_by(1.25),
+						Some(winit::event::VirtualKeyCode::Minus) if is_pressed(state) => editor.camera_mut().zoom_by(0.8),
+						// The level can't be edited while playtesting
+						_ if editor.is_playtesting() => (),
+						// Ctrl+Arrow grows the map at that edge, Ctrl+Shift+Arrow shrinks it
+						Some(winit::event::VirtualKeyCode::Left) if is_pressed(state) && modifiers.ctrl() => { editor.resize(if modifiers.shift() { -1 } else { 1 }, 0, 0, 0); }
+						Some(winit::event::VirtualKeyCode::Up) if is_pressed(state) && modifiers.ctrl() => { editor.resize(0, if modifiers.shift() { -1 } else { 1 }, 0, 0); }
+						Some(winit::event::VirtualKeyCode::Right) if is_pressed(state) && modifiers.ctrl() => { editor.resize(0, 0, if modifiers.shift() { -1 } else { 1 }, 0); }
+						Some(winit::event::VirtualKeyCode::Down) if is_pressed(state) && modifiers.ctrl() => { editor.resize(0, 0, 0, if modifiers.shift() { -1 } else { 1 }); }
 						Some(winit::event::VirtualKeyCode::Left) => input.left = is_pressed(state),
 						Some(winit::event::VirtualKeyCode::Right) => input.right = is_pressed(state),
 						Some(winit::event::VirtualKeyCode::Up) => input.up = is_pressed(state),
 						Some(winit::event::VirtualKeyCode::Down) => input.down = is_pressed(state),
-						Some(winit::event::VirtualKeyCode::A) if is_pressed(state) => input.chr = Some('A'),
-						Some(winit::event::VirtualKeyCode::B) if is_pressed(state) => input.chr = Some('B'),
-						Some(winit::event::VirtualKeyCode::C) if is_pressed(state) => input.chr = Some('C'),
-						Some(winit::event::VirtualKeyCode::D) if is_pressed(state) => input.chr = Some('D'),
-						Some(winit::event::VirtualKeyCode::E) if is_pressed(state) => input.chr = Some('E'),
-						Some(winit::event::VirtualKeyCode::F) if is_pressed(state) => input.chr = Some('F'),
-						Some(winit::event::VirtualKeyCode::G) if is_pressed(state) => input.chr = Some('G'),
-						Some(winit::event::VirtualKeyCode::H) if is_pressed(state) => input.chr = Some('H'),
-						Some(winit::event::VirtualKeyCode::I) if is_pressed(state) => input.chr = Some('I'),
-						Some(winit::event::VirtualKeyCode::J) if is_pressed(state) => input.chr = Some('J'),
-						Some(winit::event::VirtualKeyCode::K) if is_pressed(state) => input.chr = Some('K'),
-						Some(winit::event::VirtualKeyCode::L) if is_pressed(state) => input.chr = Some('L'),
-						Some(winit::event::VirtualKeyCode::M) if is_pressed(state) => input.chr = Some('M'),
-						Some(winit::event::VirtualKeyCode::N) if is_pressed(state) => input.chr = Some('N'),
-						Some(winit::event::VirtualKeyCode::O) if is_pressed(state) => input.chr = Some('O'),
-						Some(winit::event::VirtualKeyCode::P) if is_pressed(state) => input.chr = Some('P'),
-						Some(winit::event::VirtualKeyCode::Q) if is_pressed(state) => input.chr = Some('Q'),
-						Some(winit::event::VirtualKeyCode::R) if is_pressed(state) => input.chr = Some('R'),
-						Some(winit::event::VirtualKeyCode::S) if is_pressed(state) => input.chr = Some('S'),
-						Some(winit::event::VirtualKeyCode::T) if is_pressed(state) => input.chr = Some('T'),
-						Some(winit::event::VirtualKeyCode::U) if is_pressed(state) => input.chr = Some('U'),
-						Some(winit::event::VirtualKeyCode::V) if is_pressed(state) => input.chr = Some('V'),
-						Some(winit::event::VirtualKeyCode::W) if is_pressed(state) => input.chr = Some('W'),
-						Some(winit::event::VirtualKeyCode::X) if is_pressed(state) => input.chr = Some('X'),
-						Some(winit::event::VirtualKeyCode::Y) if is_pressed(state) => input.chr = Some('Y'),
-						Some(winit::event::VirtualKeyCode::Z) if is_pressed(state) => input.chr = Some('Z'),
+						Some(winit::event::VirtualKeyCode::Z) if is_pressed(state) && modifiers.ctrl() => {
+							if modifiers.shift() { editor.document_mut().redo(); } else { editor.document_mut().undo(); }
+						}
+						Some(winit::event::VirtualKeyCode::Y) if is_pressed(state) && modifiers.ctrl() => {
+							editor.document_mut().redo();
+						}
+						Some(winit::event::VirtualKeyCode::C) if is_pressed(state) && modifiers.ctrl() => { editor.copy(); }
+						Some(winit::event::VirtualKeyCode::X) if is_pressed(state) && modifiers.ctrl() => { editor.cut(); }
+						Some(winit::event::VirtualKeyCode::V) if is_pressed(state) && modifiers.ctrl() => { editor.paste(); }
+						Some(winit::event::VirtualKeyCode::R) if is_pressed(state) && modifiers.ctrl() => {
+							if let Err(err) = editor.document_mut().rotate_clipboard() { eprintln!("{}", err); }
+						}
+						Some(winit::event::VirtualKeyCode::M) if is_pressed(state) && modifiers.ctrl() => {
+							if let Err(err) = editor.document_mut().mirror_clipboard() { eprintln!("{}", err); }
+						}
+						Some(winit::event::VirtualKeyCode::Key1) if is_pressed(state) => editor.set_mode(chipgame::editor::DrawMode::Pencil),
+						Some(winit::event::VirtualKeyCode::Key2) if is_pressed(state) => editor.set_mode(chipgame::editor::DrawMode::Line),
+						Some(winit::event::VirtualKeyCode::Key3) if is_pressed(state) => editor.set_mode(chipgame::editor::DrawMode::Rect),
+						Some(winit::event::VirtualKeyCode::Key4) if is_pressed(state) => editor.set_mode(chipgame::editor::DrawMode::Flood),
+						Some(winit::event::VirtualKeyCode::Key5) if is_pressed(state) => editor.set_mode(chipgame::editor::DrawMode::Select),
+						Some(winit::event::VirtualKeyCode::Tab) if is_pressed(state) => {
+							if modifiers.shift() { editor.palette_mut().prev(); } else { editor.palette_mut().next(); }
+						}
+						Some(winit::event::VirtualKeyCode::F) if is_pressed(state) => editor.palette_mut().cycle_facing(),
+						Some(winit::event::VirtualKeyCode::E) if is_pressed(state) => editor.palette_mut().select_erase(),
+						// I shows the level properties, F2 and F3 type the name and hint
+						Some(winit::event::VirtualKeyCode::I) if is_pressed(state) => editor.toggle_props(),
+						Some(winit::event::VirtualKeyCode::F2) if is_pressed(state) => editor.begin_text(chipgame::editor::TextField::Name),
+						Some(winit::event::VirtualKeyCode::F3) if is_pressed(state) => editor.begin_text(chipgame::editor::TextField::Hint),
+						// T adds 10 seconds to the time limit and C adds a chip, with Shift they subtract
+						Some(winit::event::VirtualKeyCode::T) if is_pressed(state) => {
+							let step = if modifiers.shift() { -10 } else { 10 };
+							editor.document_mut().edit_properties(|props| props.time = i32::max(0, props.time + step));
+						}
+						Some(winit::event::VirtualKeyCode::C) if is_pressed(state) => {
+							let step = if modifiers.shift() { -1 } else { 1 };
+							editor.document_mut().edit_properties(|props| props.chips = i32::max(0, props.chips + step));
+						}
+						Some(winit::event::VirtualKeyCode::F6) if is_pressed(state) => {
+							editor.document_mut().update_chips();
+							println!("Chips: {}", editor.document().properties().chips);
+						}
+						Some(winit::event::VirtualKeyCode::F7) if is_pressed(state) => {
+							editor.document_mut().generate_password(&level_passwords(&file_path));
+							println!("Password: {}", editor.document().properties().password);
+						}
+						// L shows the connection list, PageUp/PageDown highlights one and Delete removes it
+						Some(winit::event::VirtualKeyCode::L) if is_pressed(state) => editor.toggle_conn_list(),
+						Some(winit::event::VirtualKeyCode::PageDown) if is_pressed(state) => editor.next_conn(),
+						Some(winit::event::VirtualKeyCode::PageUp) if is_pressed(state) => editor.prev_conn(),
+						Some(winit::event::VirtualKeyCode::Delete) if is_pressed(state) => { editor.delete_conn(); }
+						Some(winit::event::VirtualKeyCode::F8) if is_pressed(state) => {
+							editor.document_mut().reseed();
+							println!("Seed: {}", editor.document().properties().seed);
+						}
 						Some(winit::event::VirtualKeyCode::F5) if is_pressed(state) => {
-							let s = editor.save_level();
+							for index in editor.document().invalid_connections() {
+								println!("Warning: connection {} is invalid", index);
+							}
+							let s = editor.document().save();
 							fs::write(&file_path, s).unwrap();
 						}
 						_ => (),
 					}
 				}
+				winit::event::Event::WindowEvent { event: winit::event::WindowEvent::ReceivedCharacter(chr), .. } => {
+					editor.type_char(chr);
+				}
 				winit::event::Event::WindowEvent { event: winit::event::WindowEvent::MouseInput { state, button, .. }, .. } => {
 					match button {
 						winit::event::MouseButton::Left => input.left_click = is_pressed(state),
 						winit::event::MouseButton::Right => input.right_click = is_pressed(state),
+						winit::event::MouseButton::Middle => panning = is_pressed(state),
 						_ => (),
 					}
 				}
 				winit::event::Event::WindowEvent { event: winit::event::WindowEvent::CursorMoved { position, .. }, .. } => {
-					input.mouse.x = position.x as i32;
-					input.mouse.y = position.y as i32;
+					let mouse = cvmath::Vec2(position.x as i32, position.y as i32);
+					if panning {
+						editor.camera_mut().pan_drag(input.mouse, mouse);
+					}
+					input.mouse = mouse;
 				}
 				winit::event::Event::MainEventsCleared => {
 					*control_flow = winit::event_loop::ControlFlow::Exit;
@@ -133,14 +229,18 @@ fn main() {
 		});
 
 		input.screen_size = cvmath::Vec2(size.width as i32, size.height as i32);
+		let now = time::Instant::now();
+		input.dt = (now - frame_time).as_secs_f32();
+		frame_time = now;
+		if let Some(gilrs) = &mut gilrs {
+			gamepad::poll_gamepads(gilrs, &mut input_map);
+		}
+		input.play = input_map.input();
 
-		editor.init(chipgame::visual::Resources {
-			tileset,
-			tileset_size: [tex_info.width, tex_info.height].into(),
-			shader,
-			screen_size: [size.width as i32, size.height as i32].into(),
-		});
-		editor.render(&mut g, &input);
+		for err in editor.hot_reload(&mut g) {
+			eprintln!("Hot reload failed: {}", err);
+		}
+		editor.render(&mut chipgame::visual::ShadeBackend::new(&mut g), &input);
 
 		// Swap the buffers and wait for the next frame
 		context.swap_buffers().unwrap();
@@ -159,3 +259,18 @@ fn is_pressed(state: winit::event::ElementState) -> bool {
 		winit::event::ElementState::Released => false,
 	}
 }
+
+/// Collects the passwords of the other levels next to the edited level.
+fn level_passwords(file_path: &std::ffi::OsStr) -> Vec<String> {
+	let path = std::path::Path::new(file_path);
+	let Some(dir) = path.parent() else { return Vec::new() };
+	let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+	entries.filter_map(|entry| {
+		let entry_path = entry.ok()?.path();
+		if entry_path == path {
+			return None;
+		}
+		let ld = chipgame::core::dto::LevelDto::parse(&fs::read_to_string(&entry_path).ok()?).ok()?;
+		Some(ld.password)
+	}).collect()
+}
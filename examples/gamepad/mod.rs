@@ -0,0 +1,53 @@
+//! Gamepad input shared by the examples.
+
+/// Feeds the gamepad events to the input map.
+pub fn poll_gamepads(gilrs: &mut gilrs::Gilrs, input_map: &mut chipgame::bindings::InputMap) {
+	while let Some(gilrs::Event { event, .. }) = gilrs.next_event() {
+		match event {
+			gilrs::EventType::ButtonPressed(button, _) => if let Some(button) = gamepad_button(button) { input_map.button(button, true) },
+			gilrs::EventType::ButtonReleased(button, _) => if let Some(button) = gamepad_button(button) { input_map.button(button, false) },
+			gilrs::EventType::AxisChanged(axis, value, _) => if let Some(axis) = gamepad_axis(axis) { input_map.axis(axis, value) },
+			gilrs::EventType::Disconnected => input_map.release_all(),
+			_ => (),
+		}
+	}
+}
+
+fn gamepad_button(button: gilrs::Button) -> Option<chipgame::bindings::GamepadButton> {
+	use chipgame::bindings::GamepadButton;
+	let button = match button {
+		gilrs::Button::South => GamepadButton::South,
+		gilrs::Button::East => GamepadButton::East,
+		gilrs::Button::North => GamepadButton::North,
+		gilrs::Button::West => GamepadButton::West,
+		gilrs::Button::LeftTrigger => GamepadButton::LeftTrigger,
+		gilrs::Button::LeftTrigger2 => GamepadButton::LeftTrigger2,
+		gilrs::Button::RightTrigger => GamepadButton::RightTrigger,
+		gilrs::Button::RightTrigger2 => GamepadButton::RightTrigger2,
+		gilrs::Button::Select => GamepadButton::Select,
+		gilrs::Button::Start => GamepadButton::Start,
+		gilrs::Button::Mode => GamepadButton::Mode,
+		gilrs::Button::LeftThumb => GamepadButton::LeftThumb,
+		gilrs::Button::RightThumb => GamepadButton::RightThumb,
+		gilrs::Button::DPadUp => GamepadButton::DPadUp,
+		gilrs::Button::DPadDown => GamepadButton::DPadDown,
+		gilrs::Button::DPadLeft => GamepadButton::DPadLeft,
+		gilrs::Button::DPadRight => GamepadButton::DPadRight,
+		_ => return None,
+	};
+	Some(button)
+}
+
+fn gamepad_axis(axis: gilrs::Axis) -> Option<chipgame::bindings::GamepadAxis> {
+	use chipgame::bindings::GamepadAxis;
+	let axis = match axis {
+		gilrs::Axis::LeftStickX => GamepadAxis::LeftStickX,
+		gilrs::Axis::LeftStickY => GamepadAxis::LeftStickY,
+		gilrs::Axis::RightStickX => GamepadAxis::RightStickX,
+		gilrs::Axis::RightStickY => GamepadAxis::RightStickY,
+		gilrs::Axis::LeftZ => GamepadAxis::LeftZ,
+		gilrs::Axis::RightZ => GamepadAxis::RightZ,
+		_ => return None,
+	};
+	Some(axis)
+}
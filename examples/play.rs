@@ -1,5 +1,7 @@
 use std::{fs, thread, time};
 
+mod gamepad;
+
 fn main() {
 	let Some(file_path) = std::env::args_os().nth(1) else {
 		panic!("Usage: cargo run --example editor <level>");
@@ -23,27 +25,53 @@ fn main() {
 	// Create the graphics context
 	let mut g = shade::gl::GlGraphics::new();
 
-	// Load the texture
-	let tileset = shade::png::load(&mut g, Some("scene tiles"), "data/Color_Tileset.png", &shade::png::TextureProps {
-		filter_min: shade::TextureFilter::Linear,
-		filter_mag: shade::TextureFilter::Linear,
+	let data = fs::read_to_string("data/data.json").unwrap();
+
+	// Load the tilesets
+	let mut skins = Vec::new();
+	for atlas in chipgame::visual::SpriteAtlas::parse_all(&data).unwrap() {
+		let path = format!("data/{}", atlas.texture);
+		skins.push(chipgame::visual::Skin::load(&mut g, atlas, &path).unwrap());
+	}
+
+	// Load the font, its pixels are drawn without filtering
+	let font_atlas = chipgame::visual::FontAtlas::parse_all(&data).unwrap().swap_remove(0);
+	let font_texture = shade::png::load(&mut g, Some(font_atlas.name.as_str()), &format!("data/{}", font_atlas.texture), &shade::png::TextureProps {
+		filter_min: shade::TextureFilter::Nearest,
+		filter_mag: shade::TextureFilter::Nearest,
 		wrap_u: shade::TextureWrap::ClampEdge,
 		wrap_v: shade::TextureWrap::ClampEdge,
-	}, Some(&mut shade::png::gutter(32, 32, 4))).unwrap();
-	let tex_info = g.texture2d_get_info(tileset).unwrap();
+	}, None).unwrap();
+	let tex_info = g.texture2d_get_info(font_texture).unwrap();
+	let font = chipgame::visual::Font::new(font_atlas, font_texture, [tex_info.width, tex_info.height].into()).unwrap();
 
-	// Create the shader
-	let shader = g.shader_create(None).unwrap();
-	if let Err(_) = g.shader_compile(shader, include_str!("../data/standard.vs.glsl"), include_str!("../data/standard.fs.glsl")) {
-		panic!("Failed to compile shader: {}", g.shader_compile_log(shader).unwrap());
-	}
+	// Load the entity meshes
+	let meshes = chipgame::visual::EntityMesh::load_all("data", &data).unwrap();
 
 	let mut past_now = time::Instant::now();
+	let mut frame_time = past_now;
 
 	let mut state = chipgame::visual::VisualState::default();
 	state.init();
+	state.load_data(&data).unwrap();
+	state.resources.load_shader(&mut g, &fs::read_to_string("data/standard.vs.glsl").unwrap(), &fs::read_to_string("data/standard.fs.glsl").unwrap()).unwrap();
+	for skin in skins {
+		state.resources.add_skin(skin);
+	}
+	state.resources.set_font(&font);
+	for mesh in meshes {
+		state.resources.set_mesh(mesh);
+	}
+	// Changes to the shaders, the tileset and the data file are reloaded while running
+	state.resources.watch("data");
 	state.load_level(&fs::read_to_string(&file_path).unwrap());
-	let mut input = chipgame::core::Input::default();
+	// Keyboard and gamepad input is mapped to the game through the bindings file
+	let mut input_map = chipgame::bindings::InputMap::new(chipgame::bindings::Bindings::parse(&fs::read_to_string("data/bindings.json").unwrap()).unwrap());
+	let mut gilrs = gilrs::Gilrs::new().ok();
+
+	// Dragging with the middle mouse button pans the camera
+	let mut mouse = cvmath::Vec2(0, 0);
+	let mut panning = false;
 
 	// Main loop
 	let mut quit = false;
@@ -66,25 +94,37 @@ fn main() {
 				winit::event::Event::WindowEvent { event: winit::event::WindowEvent::CloseRequested, .. } => {
 					quit = true;
 				}
+				winit::event::Event::WindowEvent { event: winit::event::WindowEvent::Focused(false), .. } => {
+					input_map.release_all();
+				}
 				winit::event::Event::WindowEvent { event: winit::event::WindowEvent::KeyboardInput { input: keyboard_input, .. }, .. } => {
-					if keyboard_input.virtual_keycode == Some(winit::event::VirtualKeyCode::Left) {
-						input.left = keyboard_input.state == winit::event::ElementState::Pressed;
+					// Keys are bound to actions by the names of their key codes
+					if let Some(keycode) = keyboard_input.virtual_keycode {
+						input_map.key(&format!("{:?}", keycode), keyboard_input.state == winit::event::ElementState::Pressed);
 					}
-					if keyboard_input.virtual_keycode == Some(winit::event::VirtualKeyCode::Right) {
-						input.right = keyboard_input.state == winit::event::ElementState::Pressed;
+					// O switches between the perspective and top-down view, +/- zooms
+					if keyboard_input.virtual_keycode == Some(winit::event::VirtualKeyCode::O) && keyboard_input.state == winit::event::ElementState::Pressed {
+						state.camera.toggle_projection();
 					}
-					if keyboard_input.virtual_keycode == Some(winit::event::VirtualKeyCode::Up) {
-						input.up = keyboard_input.state == winit::event::ElementState::Pressed;
+					if keyboard_input.virtual_keycode == Some(winit::event::VirtualKeyCode::Equals) && keyboard_input.state == winit::event::ElementState::Pressed {
+						state.camera.zoom_by(1.25);
 					}
-					if keyboard_input.virtual_keycode == Some(winit::event::VirtualKeyCode::Down) {
-						input.down = keyboard_input.state == winit::event::ElementState::Pressed;
+					if keyboard_input.virtual_keycode == Some(winit::event::VirtualKeyCode::Minus) && keyboard_input.state == winit::event::ElementState::Pressed {
+						state.camera.zoom_by(0.8);
 					}
-					if keyboard_input.virtual_keycode == Some(winit::event::VirtualKeyCode::A) {
-						input.a = keyboard_input.state == winit::event::ElementState::Pressed;
+					if keyboard_input.virtual_keycode == Some(winit::event::VirtualKeyCode::Tab) && keyboard_input.state == winit::event::ElementState::Pressed {
+						state.resources.next_skin();
 					}
-					if keyboard_input.virtual_keycode == Some(winit::event::VirtualKeyCode::B) {
-						input.b = keyboard_input.state == winit::event::ElementState::Pressed;
+				}
+				winit::event::Event::WindowEvent { event: winit::event::WindowEvent::MouseInput { state: button_state, button: winit::event::MouseButton::Middle, .. }, .. } => {
+					panning = button_state == winit::event::ElementState::Pressed;
+				}
+				winit::event::Event::WindowEvent { event: winit::event::WindowEvent::CursorMoved { position, .. }, .. } => {
+					let new_mouse = cvmath::Vec2(position.x as i32, position.y as i32);
+					if panning {
+						state.camera.pan_drag(mouse, new_mouse);
 					}
+					mouse = new_mouse;
 				}
 				winit::event::Event::MainEventsCleared => {
 					*control_flow = winit::event_loop::ControlFlow::Exit;
@@ -93,14 +133,18 @@ fn main() {
 			}
 		});
 
-		state.resources = chipgame::visual::Resources {
-			tileset,
-			tileset_size: [tex_info.width, tex_info.height].into(),
-			shader,
-			screen_size: [size.width as i32, size.height as i32].into(),
-		};
-		state.update(&input);
-		state.draw(&mut g);
+		state.resources.screen_size = [size.width as i32, size.height as i32].into();
+		// The game ticks at a fixed rate regardless of the frame rate
+		let now = time::Instant::now();
+		if let Some(gilrs) = &mut gilrs {
+			gamepad::poll_gamepads(gilrs, &mut input_map);
+		}
+		state.advance(&input_map.input(), (now - frame_time).as_secs_f32());
+		frame_time = now;
+		for err in state.hot_reload(&mut g) {
+			eprintln!("Hot reload failed: {}", err);
+		}
+		state.draw(&mut chipgame::visual::ShadeBackend::new(&mut g));
 
 		// Swap the buffers and wait for the next frame
 		context.swap_buffers().unwrap();
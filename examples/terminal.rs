@@ -0,0 +1,294 @@
+use std::{fs, io, panic, thread, time};
+use std::io::Write as _;
+use chipgame::core;
+use crossterm::{cursor, event, queue, style, terminal};
+use crossterm::style::Color;
+
+const TICK: time::Duration = time::Duration::from_nanos(1_000_000_000 / core::TICK_RATE as u64);
+
+fn main() {
+	let Some(file_path) = std::env::args_os().nth(1) else {
+		panic!("Usage: cargo run --example terminal <level>");
+	};
+	let json = fs::read_to_string(&file_path).unwrap();
+
+	let mut stdout = io::stdout();
+	let guard = TerminalGuard::new(&mut stdout).unwrap();
+	run(&mut stdout, &json, guard.key_release).unwrap();
+}
+
+/// Restores the terminal when dropped, also when the game panics.
+struct TerminalGuard {
+	key_release: bool,
+}
+
+impl TerminalGuard {
+	fn new(stdout: &mut io::Stdout) -> io::Result<TerminalGuard> {
+		terminal::enable_raw_mode()?;
+		// Terminals without key release events only report presses and key repeats
+		let key_release = terminal::supports_keyboard_enhancement().unwrap_or(false);
+		let guard = TerminalGuard { key_release };
+
+		// Restore the terminal before the panic message is printed, otherwise it is lost with the alternate screen
+		let hook = panic::take_hook();
+		panic::set_hook(Box::new(move |info| {
+			restore(key_release);
+			hook(info);
+		}));
+
+		queue!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+		if key_release {
+			queue!(stdout, event::PushKeyboardEnhancementFlags(event::KeyboardEnhancementFlags::REPORT_EVENT_TYPES))?;
+		}
+		Ok(guard)
+	}
+}
+
+impl Drop for TerminalGuard {
+	fn drop(&mut self) {
+		restore(self.key_release);
+	}
+}
+
+fn restore(key_release: bool) {
+	let mut stdout = io::stdout();
+	if key_release {
+		let _ = queue!(stdout, event::PopKeyboardEnhancementFlags);
+	}
+	let _ = queue!(stdout, style::ResetColor, cursor::Show, terminal::LeaveAlternateScreen);
+	let _ = stdout.flush();
+	let _ = terminal::disable_raw_mode();
+}
+
+struct Frontend {
+	game: core::GameState,
+	input: core::Input,
+	hint_pos: Option<cvmath::Vec2i>,
+}
+
+fn run(stdout: &mut io::Stdout, json: &str, key_release: bool) -> io::Result<()> {
+	let mut fe = Frontend {
+		game: core::GameState::default(),
+		input: core::Input::default(),
+		hint_pos: None,
+	};
+	fe.game.load(json);
+
+	let mut next_tick = time::Instant::now();
+	loop {
+		// Without key release events every key press is a single tick pulse
+		if !key_release {
+			fe.input = core::Input::default();
+		}
+
+		while event::poll(time::Duration::ZERO)? {
+			let event::Event::Key(key) = event::read()? else { continue };
+			let is = key.kind != event::KeyEventKind::Release;
+			match key.code {
+				event::KeyCode::Left => fe.input.left = is,
+				event::KeyCode::Right => fe.input.right = is,
+				event::KeyCode::Up => fe.input.up = is,
+				event::KeyCode::Down => fe.input.down = is,
+				event::KeyCode::Char('r') if is => {
+					fe.game = core::GameState::default();
+					fe.game.load(json);
+					fe.hint_pos = None;
+				}
+				event::KeyCode::Esc | event::KeyCode::Char('q') => return Ok(()),
+				event::KeyCode::Char('c') if is && key.modifiers.contains(event::KeyModifiers::CONTROL) => return Ok(()),
+				_ => (),
+			}
+		}
+
+		fe.game.tick(&fe.input);
+		for ev in &fe.game.events {
+			if let &core::GameEvent::PlayerHint { pos, .. } = ev {
+				fe.hint_pos = Some(pos);
+			}
+		}
+
+		// Render at half the simulation rate
+		if fe.game.time % 2 == 0 {
+			draw(stdout, &fe)?;
+		}
+
+		next_tick += TICK;
+		let now = time::Instant::now();
+		if next_tick > now {
+			thread::sleep(next_tick - now);
+		}
+		else {
+			next_tick = now;
+		}
+	}
+}
+
+fn draw(stdout: &mut io::Stdout, fe: &Frontend) -> io::Result<()> {
+	let game = &fe.game;
+	let (cols, rows) = terminal::size()?;
+	// Each tile is two characters wide, leave room for the status lines
+	let view_w = cols as i32 / 2;
+	let view_h = i32::max(1, rows as i32 - 3);
+
+	let player_pos = game.ents.get(game.ps.entity).map(|ent| ent.pos).unwrap_or_default();
+	let origin_x = i32::max(0, i32::min(player_pos.x - view_w / 2, game.field.width - view_w));
+	let origin_y = i32::max(0, i32::min(player_pos.y - view_h / 2, game.field.height - view_h));
+
+	for row in 0..view_h {
+		queue!(stdout, cursor::MoveTo(0, row as u16))?;
+		for col in 0..view_w {
+			let pos = cvmath::Vec2i(origin_x + col, origin_y + row);
+			let (text, fg, bg) = tile_chars(game, pos);
+			queue!(stdout, style::SetForegroundColor(fg), style::SetBackgroundColor(bg), style::Print(text))?;
+		}
+	}
+	queue!(stdout, style::ResetColor)?;
+
+	// Status lines
+	let ps = &game.ps;
+	let chips_left = i32::max(0, game.field.chips - ps.chips);
+	let time_left = if game.field.time > 0 { format!("{}", i32::max(0, game.field.time - game.time / core::TICK_RATE as i32)) } else { String::from("--") };
+	let boots = [(ps.flippers, "Flippers"), (ps.fire_boots, "FireBoots"), (ps.ice_skates, "IceSkates"), (ps.suction_boots, "SuctionBoots")]
+		.iter().filter(|(has, _)| *has).map(|&(_, name)| name).collect::<Vec<_>>().join(" ");
+	let status = format!("{}  Chips left: {}  Time: {}  Keys: B{} R{} G{} Y{}  {}",
+		game.field.name, chips_left, time_left, ps.keys[0], ps.keys[1], ps.keys[2], ps.keys[3], boots);
+	let message = match ps.action {
+		core::PlayerAction::Win => String::from("Level complete! Press R to restart, Q to quit."),
+		core::PlayerAction::Burn | core::PlayerAction::Drown | core::PlayerAction::Death => String::from("Game over! Press R to restart, Q to quit."),
+		_ if fe.hint_pos.is_some() && fe.hint_pos == Some(player_pos) => format!("Hint: {}", game.field.hint),
+		_ => String::new(),
+	};
+
+	queue!(stdout,
+		cursor::MoveTo(0, view_h as u16), terminal::Clear(terminal::ClearType::FromCursorDown),
+		cursor::MoveTo(0, view_h as u16 + 1), style::Print(truncate(&status, cols as usize)),
+		cursor::MoveTo(0, view_h as u16 + 2), style::Print(truncate(&message, cols as usize)),
+	)?;
+	stdout.flush()
+}
+
+fn truncate(s: &str, len: usize) -> &str {
+	match s.char_indices().nth(len) {
+		Some((i, _)) => &s[..i],
+		None => s,
+	}
+}
+
+fn tile_chars(game: &core::GameState, pos: cvmath::Vec2i) -> (&'static str, Color, Color) {
+	// Draw the topmost visible entity, blocks hide what is under them
+	let mut top: Option<&core::Entity> = None;
+	for ent in game.ents.map.values() {
+		if ent.pos != pos || ent.hidden {
+			continue;
+		}
+		if top.map(|top| entity_layer(ent.kind) > entity_layer(top.kind)).unwrap_or(true) {
+			top = Some(ent);
+		}
+	}
+	let (_, _, bg) = terrain_chars(game.field.get_terrain(pos));
+	if let Some(ent) = top {
+		let (text, fg) = entity_chars(ent, &game.ps);
+		return (text, fg, bg);
+	}
+	terrain_chars(game.field.get_terrain(pos))
+}
+
+fn entity_layer(kind: core::EntityKind) -> i32 {
+	match kind {
+		core::EntityKind::Block => 3,
+		core::EntityKind::Player => 2,
+		core::EntityKind::Chip | core::EntityKind::Socket | core::EntityKind::Bomb | core::EntityKind::Thief => 0,
+		core::EntityKind::Flippers | core::EntityKind::FireBoots | core::EntityKind::IceSkates | core::EntityKind::SuctionBoots => 0,
+		core::EntityKind::BlueKey | core::EntityKind::RedKey | core::EntityKind::GreenKey | core::EntityKind::YellowKey => 0,
+		_ => 1,
+	}
+}
+
+fn entity_chars(ent: &core::Entity, ps: &core::PlayerState) -> (&'static str, Color) {
+	let dir = |up, left, down, right| match ent.face_dir {
+		Some(core::Dir::Up) => up,
+		Some(core::Dir::Left) => left,
+		Some(core::Dir::Down) => down,
+		Some(core::Dir::Right) => right,
+		None => down,
+	};
+	match ent.kind {
+		core::EntityKind::Player => match ps.action {
+			core::PlayerAction::Burn | core::PlayerAction::Drown | core::PlayerAction::Death => ("XX", Color::Red),
+			core::PlayerAction::Win => ("\\o", Color::Yellow),
+			_ => (dir("@^", "<@", "@v", "@>"), Color::Yellow),
+		},
+		core::EntityKind::Chip => ("$$", Color::Yellow),
+		core::EntityKind::Socket => ("[]", Color::Green),
+		core::EntityKind::Block => ("[]", Color::DarkYellow),
+		core::EntityKind::Flippers => ("Fl", Color::Blue),
+		core::EntityKind::FireBoots => ("Fb", Color::Red),
+		core::EntityKind::IceSkates => ("Is", Color::Cyan),
+		core::EntityKind::SuctionBoots => ("Sb", Color::Green),
+		core::EntityKind::BlueKey => ("k-", Color::Blue),
+		core::EntityKind::RedKey => ("k-", Color::Red),
+		core::EntityKind::GreenKey => ("k-", Color::Green),
+		core::EntityKind::YellowKey => ("k-", Color::Yellow),
+		core::EntityKind::Thief => ("Th", Color::Magenta),
+		core::EntityKind::Bomb => ("()", Color::Red),
+		core::EntityKind::Bug => (dir("b^", "<b", "bv", "b>"), Color::Red),
+		core::EntityKind::FireBall => ("**", Color::Red),
+		core::EntityKind::PinkBall => ("oo", Color::Magenta),
+		core::EntityKind::Tank => (dir("T^", "<T", "Tv", "T>"), Color::Blue),
+		core::EntityKind::Glider => (dir("g^", "<g", "gv", "g>"), Color::Cyan),
+		core::EntityKind::Teeth => (dir("W^", "<W", "Wv", "W>"), Color::Red),
+		core::EntityKind::Walker => ("Wk", Color::Green),
+		core::EntityKind::Blob => ("Bl", Color::Green),
+		core::EntityKind::Paramecium => (dir("p^", "<p", "pv", "p>"), Color::Yellow),
+	}
+}
+
+fn terrain_chars(terrain: core::Terrain) -> (&'static str, Color, Color) {
+	match terrain {
+		core::Terrain::Blank => ("  ", Color::Black, Color::Black),
+		core::Terrain::Floor => (" .", Color::DarkGrey, Color::Black),
+		core::Terrain::Wall => ("##", Color::Grey, Color::DarkGrey),
+		core::Terrain::Socket => ("[]", Color::Green, Color::Black),
+		core::Terrain::BlueLock => ("[]", Color::White, Color::Blue),
+		core::Terrain::RedLock => ("[]", Color::White, Color::Red),
+		core::Terrain::GreenLock => ("[]", Color::White, Color::Green),
+		core::Terrain::YellowLock => ("[]", Color::Black, Color::Yellow),
+		core::Terrain::Hint => (" ?", Color::Cyan, Color::Black),
+		core::Terrain::Exit => ("<>", Color::White, Color::Blue),
+		core::Terrain::Water => ("~~", Color::Blue, Color::DarkBlue),
+		core::Terrain::Fire => ("^^", Color::Yellow, Color::DarkRed),
+		core::Terrain::Dirt => (",,", Color::DarkYellow, Color::Black),
+		core::Terrain::Gravel => (": ", Color::Grey, Color::Black),
+		core::Terrain::Ice => ("  ", Color::White, Color::DarkCyan),
+		core::Terrain::IceNW => ("/ ", Color::White, Color::DarkCyan),
+		core::Terrain::IceNE => (" \\", Color::White, Color::DarkCyan),
+		core::Terrain::IceSW => ("\\ ", Color::White, Color::DarkCyan),
+		core::Terrain::IceSE => (" /", Color::White, Color::DarkCyan),
+		core::Terrain::ForceN => ("^^", Color::Green, Color::Black),
+		core::Terrain::ForceW => ("<<", Color::Green, Color::Black),
+		core::Terrain::ForceS => ("vv", Color::Green, Color::Black),
+		core::Terrain::ForceE => (">>", Color::Green, Color::Black),
+		core::Terrain::ForceRandom => ("??", Color::Green, Color::Black),
+		core::Terrain::CloneMachine => ("CM", Color::White, Color::DarkGrey),
+		core::Terrain::ToggleFloor => ("--", Color::DarkGreen, Color::Black),
+		core::Terrain::ToggleWall => ("##", Color::Green, Color::DarkGreen),
+		core::Terrain::PanelN => ("''", Color::White, Color::Black),
+		core::Terrain::PanelW => ("| ", Color::White, Color::Black),
+		core::Terrain::PanelS => ("__", Color::White, Color::Black),
+		core::Terrain::PanelE => (" |", Color::White, Color::Black),
+		core::Terrain::PanelSE => ("_|", Color::White, Color::Black),
+		core::Terrain::HiddenWall => (" .", Color::DarkGrey, Color::Black),
+		core::Terrain::HiddenWallRevealed => ("##", Color::Grey, Color::DarkGrey),
+		core::Terrain::InvisWall => (" .", Color::DarkGrey, Color::Black),
+		core::Terrain::BlueWall => ("##", Color::Blue, Color::DarkBlue),
+		core::Terrain::BlueFake => ("##", Color::Blue, Color::DarkBlue),
+		core::Terrain::GreenButton => ("()", Color::Green, Color::Black),
+		core::Terrain::RedButton => ("()", Color::Red, Color::Black),
+		core::Terrain::BrownButton => ("()", Color::DarkYellow, Color::Black),
+		core::Terrain::BlueButton => ("()", Color::Blue, Color::Black),
+		core::Terrain::Teleport => ("@@", Color::Cyan, Color::DarkBlue),
+		core::Terrain::BearTrap => ("TT", Color::DarkYellow, Color::Black),
+		core::Terrain::RecessedWall => ("__", Color::DarkGrey, Color::Black),
+		core::Terrain::RaisedWall => ("##", Color::Grey, Color::DarkGrey),
+	}
+}
@@ -0,0 +1,30 @@
+use std::fs;
+
+fn main() {
+	let mut args = std::env::args().skip(1);
+	let (Some(level_path), Some(out_path)) = (args.next(), args.next()) else {
+		panic!("Usage: cargo run --example thumbnail <level> <out.png> [--scale <f>] [--grid] [--conns]");
+	};
+
+	let mut opts = chipgame::raster::RenderOptions::default();
+	while let Some(arg) = args.next() {
+		match &*arg {
+			"--scale" => opts.scale = args.next().and_then(|s| s.parse().ok()).expect("Invalid scale"),
+			"--grid" => opts.grid = true,
+			"--conns" => opts.connections = true,
+			_ => panic!("Unknown argument: {}", arg),
+		}
+	}
+
+	let data = fs::read_to_string("data/data.json").unwrap();
+	let atlas = chipgame::visual::SpriteAtlas::parse_all(&data).unwrap().swap_remove(0);
+	let tiles = chipgame::visual::TileSet::parse(&data).unwrap();
+	let tileset = chipgame::raster::Image::load_png(&format!("data/{}", atlas.texture)).unwrap();
+
+	let mut game = chipgame::core::GameState::default();
+	game.field.defs = chipgame::core::TerrainDefs::parse(&data).unwrap();
+	game.load(&fs::read_to_string(&level_path).unwrap());
+
+	let image = chipgame::raster::render_level(&game, &tileset, &atlas, &tiles.edit, &opts);
+	image.save_png(&out_path).unwrap();
+}
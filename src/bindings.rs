@@ -0,0 +1,186 @@
+use std::collections::{HashMap, HashSet};
+use crate::core;
+
+/// Game action triggered by device input.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Action {
+	Left,
+	Right,
+	Up,
+	Down,
+	A,
+	B,
+	/// Toggles walking through walls for testing levels.
+	DevWtw,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum GamepadButton {
+	South,
+	East,
+	North,
+	West,
+	LeftTrigger,
+	LeftTrigger2,
+	RightTrigger,
+	RightTrigger2,
+	Select,
+	Start,
+	Mode,
+	LeftThumb,
+	RightThumb,
+	DPadUp,
+	DPadDown,
+	DPadLeft,
+	DPadRight,
+}
+
+/// Gamepad axis in the range -1.0 to 1.0, the y axes of the sticks point up.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum GamepadAxis {
+	LeftStickX,
+	LeftStickY,
+	RightStickX,
+	RightStickY,
+	LeftZ,
+	RightZ,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum AxisDir {
+	Negative,
+	Positive,
+}
+
+/// Device input bound to an action.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Trigger {
+	/// Keyboard key by its name, eg. `Left` or `A` as winit names its virtual key codes.
+	Key(String),
+	Button(GamepadButton),
+	/// Axis pushed past the deadzone in the direction.
+	Axis(GamepadAxis, AxisDir),
+	/// All the triggers held at once.
+	Chord(Vec<Trigger>),
+}
+
+impl Trigger {
+	/// Returns if the trigger contains an empty chord, which would always be triggered.
+	fn has_empty_chord(&self) -> bool {
+		match self {
+			Trigger::Chord(triggers) => triggers.is_empty() || triggers.iter().any(Trigger::has_empty_chord),
+			_ => false,
+		}
+	}
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bindings {
+	/// Axis values within the deadzone are ignored.
+	pub deadzone: f32,
+	/// Triggers of every action, any of them triggers the action.
+	pub actions: HashMap<Action, Vec<Trigger>>,
+}
+
+static DEFAULT_BINDINGS: std::sync::OnceLock<Bindings> = std::sync::OnceLock::new();
+
+impl Default for Bindings {
+	fn default() -> Self {
+		DEFAULT_BINDINGS.get_or_init(|| Bindings::parse(include_str!("../data/bindings.json")).unwrap()).clone()
+	}
+}
+
+impl Bindings {
+	/// Parses the bindings file.
+	pub fn parse(json: &str) -> Result<Bindings, String> {
+		let bindings: Bindings = serde_json::from_str(json).map_err(|err| err.to_string())?;
+		if !(0.0..1.0).contains(&bindings.deadzone) {
+			return Err(format!("Invalid deadzone: {}", bindings.deadzone));
+		}
+		for (action, triggers) in &bindings.actions {
+			if triggers.iter().any(Trigger::has_empty_chord) {
+				return Err(format!("Empty chord bound to {:?}", action));
+			}
+		}
+		Ok(bindings)
+	}
+}
+
+/// Tracks the state of the input devices and maps it to actions.
+#[derive(Clone, Debug, Default)]
+pub struct InputMap {
+	pub bindings: Bindings,
+	keys: HashSet<String>,
+	buttons: HashSet<GamepadButton>,
+	axes: HashMap<GamepadAxis, f32>,
+}
+
+impl InputMap {
+	pub fn new(bindings: Bindings) -> InputMap {
+		InputMap { bindings, ..InputMap::default() }
+	}
+
+	pub fn key(&mut self, name: &str, pressed: bool) {
+		if pressed {
+			self.keys.insert(String::from(name));
+		}
+		else {
+			self.keys.remove(name);
+		}
+	}
+	pub fn button(&mut self, button: GamepadButton, pressed: bool) {
+		if pressed {
+			self.buttons.insert(button);
+		}
+		else {
+			self.buttons.remove(&button);
+		}
+	}
+	pub fn axis(&mut self, axis: GamepadAxis, value: f32) {
+		self.axes.insert(axis, value);
+	}
+	/// Releases all keys, buttons and axes, eg. when the window loses focus.
+	pub fn release_all(&mut self) {
+		self.keys.clear();
+		self.buttons.clear();
+		self.axes.clear();
+	}
+
+	fn is_triggered(&self, trigger: &Trigger) -> bool {
+		match trigger {
+			Trigger::Key(name) => self.keys.contains(name),
+			Trigger::Button(button) => self.buttons.contains(button),
+			&Trigger::Axis(axis, dir) => {
+				let value = self.axes.get(&axis).cloned().unwrap_or(0.0);
+				match dir {
+					AxisDir::Negative => value < -self.bindings.deadzone,
+					AxisDir::Positive => value > self.bindings.deadzone,
+				}
+			}
+			Trigger::Chord(triggers) => triggers.iter().all(|trigger| self.is_triggered(trigger)),
+		}
+	}
+
+	pub fn is_active(&self, action: Action) -> bool {
+		self.bindings.actions.get(&action).is_some_and(|triggers| triggers.iter().any(|trigger| self.is_triggered(trigger)))
+	}
+
+	/// Returns the input for the simulation.
+	pub fn input(&self) -> core::Input {
+		core::Input {
+			a: self.is_active(Action::A),
+			b: self.is_active(Action::B),
+			left: self.is_active(Action::Left),
+			right: self.is_active(Action::Right),
+			up: self.is_active(Action::Up),
+			down: self.is_active(Action::Down),
+			dev_wtw: self.is_active(Action::DevWtw),
+		}
+	}
+}
@@ -13,3 +13,44 @@ impl Connection {
 		Connection { src: self.dest, dest: self.src }
 	}
 }
+
+/// What a connection does, inferred from the terrain at its source.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ConnKind {
+	/// Red button to the clone machine it activates.
+	Clone,
+	/// Brown button to the bear trap it releases.
+	Trap,
+	/// Teleport to the teleport it sends to.
+	Teleport,
+}
+
+impl ConnKind {
+	/// Returns the kind of connection starting at the terrain.
+	pub fn from_src(terrain: Terrain) -> Option<ConnKind> {
+		match terrain {
+			Terrain::RedButton => Some(ConnKind::Clone),
+			Terrain::BrownButton => Some(ConnKind::Trap),
+			Terrain::Teleport => Some(ConnKind::Teleport),
+			_ => None,
+		}
+	}
+	/// Returns the terrain expected at the destination.
+	pub fn dest_terrain(self) -> Terrain {
+		match self {
+			ConnKind::Clone => Terrain::CloneMachine,
+			ConnKind::Trap => Terrain::BearTrap,
+			ConnKind::Teleport => Terrain::Teleport,
+		}
+	}
+	/// Checks the terrain at both ends of a connection.
+	pub fn validate(src: Terrain, dest: Terrain) -> Result<ConnKind, String> {
+		let Some(kind) = ConnKind::from_src(src) else {
+			return Err(format!("{:?} is not a button or teleport", src));
+		};
+		if dest != kind.dest_terrain() {
+			return Err(format!("{:?} connection leads to {:?} instead of {:?}", kind, dest, kind.dest_terrain()));
+		}
+		Ok(kind)
+	}
+}
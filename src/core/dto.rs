@@ -1,15 +1,87 @@
 use super::*;
 
+/// Current version of the level format.
+pub const LEVEL_VERSION: u32 = 1;
+
+/// How [`MapDto::data`] is stored in the level file.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum MapEncoding {
+	/// Array of legend indices.
+	#[default]
+	Plain,
+	/// String of `count*index` runs separated by commas.
+	Rle,
+	/// String of the legend indices packed as base64.
+	Base64,
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Debug)]
+#[serde(try_from = "MapDtoRepr", into = "MapDtoRepr")]
 pub struct MapDto {
 	pub width: i32,
 	pub height: i32,
 	pub data: Vec<u8>,
 	pub legend: Vec<Terrain>,
+	/// Encoding of the data when serialized.
+	pub encoding: MapEncoding,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum MapDataRepr {
+	Plain(Vec<u8>),
+	Packed(String),
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct MapDtoRepr {
+	width: i32,
+	height: i32,
+	data: MapDataRepr,
+	legend: Vec<Terrain>,
+}
+
+impl TryFrom<MapDtoRepr> for MapDto {
+	type Error = String;
+
+	fn try_from(repr: MapDtoRepr) -> Result<MapDto, String> {
+		let (data, encoding) = match repr.data {
+			MapDataRepr::Plain(data) => (data, MapEncoding::Plain),
+			MapDataRepr::Packed(s) => {
+				if let Some(s) = s.strip_prefix("rle:") {
+					let len = usize::try_from(repr.width).unwrap_or(0).saturating_mul(usize::try_from(repr.height).unwrap_or(0));
+					(rle_decode(s, len)?, MapEncoding::Rle)
+				}
+				else if let Some(s) = s.strip_prefix("base64:") {
+					(base64_decode(s)?, MapEncoding::Base64)
+				}
+				else {
+					return Err(String::from("Unknown map data encoding"));
+				}
+			}
+		};
+		Ok(MapDto { width: repr.width, height: repr.height, data, legend: repr.legend, encoding })
+	}
+}
+
+impl From<MapDto> for MapDtoRepr {
+	fn from(map: MapDto) -> MapDtoRepr {
+		let data = match map.encoding {
+			MapEncoding::Plain => MapDataRepr::Plain(map.data),
+			MapEncoding::Rle => MapDataRepr::Packed(format!("rle:{}", rle_encode(&map.data))),
+			MapEncoding::Base64 => MapDataRepr::Packed(format!("base64:{}", base64_encode(&map.data))),
+		};
+		MapDtoRepr { width: map.width, height: map.height, data, legend: map.legend }
+	}
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Debug)]
 pub struct LevelDto {
+	/// Level format version, levels without a version are version 0.
+	#[serde(default)]
+	pub version: u32,
 	pub name: String,
 	pub hint: String,
 	pub password: String,
@@ -20,3 +92,130 @@ pub struct LevelDto {
 	pub entities: Vec<EntityArgs>,
 	pub connections: Vec<Connection>,
 }
+
+impl LevelDto {
+	/// Parses a level in either the JSON or the text level format.
+	///
+	/// JSON levels from older versions are migrated to the current version.
+	pub fn parse(s: &str) -> Result<LevelDto, String> {
+		if s.trim_start().starts_with('{') {
+			let mut value: serde_json::Value = serde_json::from_str(s).map_err(|err| err.to_string())?;
+			migrate(&mut value)?;
+			serde_json::from_value(value).map_err(|err| err.to_string())
+		}
+		else {
+			leveltext::parse(s)
+		}
+	}
+}
+
+/// Upgrades a level from version `N` to version `N + 1`.
+type Migration = fn(&mut serde_json::Map<String, serde_json::Value>) -> Result<(), String>;
+
+/// Migrations indexed by the version they upgrade from.
+static MIGRATIONS: [Migration; LEVEL_VERSION as usize] = [
+	// Version 0 levels predate the version field and need no other changes
+	|_| Ok(()),
+];
+
+/// Upgrades a level document to the current version, one version at a time.
+pub fn migrate(value: &mut serde_json::Value) -> Result<(), String> {
+	let Some(level) = value.as_object_mut() else {
+		return Err(String::from("Level is not an object"));
+	};
+
+	let mut version = match level.get("version") {
+		None => 0,
+		Some(version) => version.as_u64().ok_or("Invalid level version")?,
+	};
+	if version > LEVEL_VERSION as u64 {
+		return Err(format!("Unsupported level version: {}", version));
+	}
+
+	while version < LEVEL_VERSION as u64 {
+		MIGRATIONS[version as usize](level)?;
+		version += 1;
+		level.insert(String::from("version"), serde_json::Value::from(version));
+	}
+	Ok(())
+}
+
+fn rle_encode(data: &[u8]) -> String {
+	use std::fmt::Write;
+
+	let mut s = String::new();
+	let mut i = 0;
+	while i < data.len() {
+		let value = data[i];
+		let count = data[i..].iter().take_while(|&&v| v == value).count();
+		if !s.is_empty() {
+			s.push(',');
+		}
+		if count > 1 {
+			let _ = write!(s, "{}*{}", count, value);
+		}
+		else {
+			let _ = write!(s, "{}", value);
+		}
+		i += count;
+	}
+	s
+}
+
+/// Decodes the runs, failing if they expand to more than `max_len` tiles.
+fn rle_decode(s: &str, max_len: usize) -> Result<Vec<u8>, String> {
+	let mut data = Vec::new();
+	if s.is_empty() {
+		return Ok(data);
+	}
+	for run in s.split(',') {
+		let invalid = || format!("Invalid map data run: {}", run);
+		let (count, value) = match run.split_once('*') {
+			Some((count, value)) => (count.trim().parse::<usize>().map_err(|_| invalid())?, value),
+			None => (1, run),
+		};
+		let value = value.trim().parse::<u8>().map_err(|_| invalid())?;
+		if count > max_len - data.len() {
+			return Err(format!("Map data runs exceed the {} tiles of the map", max_len));
+		}
+		data.resize(data.len() + count, value);
+	}
+	Ok(data)
+}
+
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(data: &[u8]) -> String {
+	let mut s = String::with_capacity(data.len().div_ceil(3) * 4);
+	for chunk in data.chunks(3) {
+		let bits = (chunk[0] as u32) << 16 | (*chunk.get(1).unwrap_or(&0) as u32) << 8 | *chunk.get(2).unwrap_or(&0) as u32;
+		for i in 0..4 {
+			if i <= chunk.len() {
+				s.push(BASE64_CHARS[(bits >> (18 - i * 6)) as usize & 0x3f] as char);
+			}
+			else {
+				s.push('=');
+			}
+		}
+	}
+	s
+}
+
+fn base64_decode(s: &str) -> Result<Vec<u8>, String> {
+	let s = s.trim_end_matches('=');
+	let mut data = Vec::with_capacity(s.len() * 3 / 4);
+	let mut bits = 0u32;
+	let mut nbits = 0;
+	for chr in s.bytes() {
+		let Some(value) = BASE64_CHARS.iter().position(|&c| c == chr) else {
+			return Err(format!("Invalid base64 character: {:?}", chr as char));
+		};
+		bits = bits << 6 | value as u32;
+		nbits += 6;
+		if nbits >= 8 {
+			nbits -= 8;
+			data.push((bits >> nbits) as u8);
+		}
+	}
+	Ok(data)
+}
@@ -31,6 +31,34 @@ pub enum EntityKind {
 	Paramecium,
 }
 
+pub const ENTITY_KIND_COUNT: usize = 23;
+
+pub static ALL_ENTITY_KINDS: [EntityKind; ENTITY_KIND_COUNT] = [
+	EntityKind::Player,
+	EntityKind::Chip,
+	EntityKind::Socket,
+	EntityKind::Block,
+	EntityKind::Flippers,
+	EntityKind::FireBoots,
+	EntityKind::IceSkates,
+	EntityKind::SuctionBoots,
+	EntityKind::BlueKey,
+	EntityKind::RedKey,
+	EntityKind::GreenKey,
+	EntityKind::YellowKey,
+	EntityKind::Thief,
+	EntityKind::Bomb,
+	EntityKind::Bug,
+	EntityKind::FireBall,
+	EntityKind::PinkBall,
+	EntityKind::Tank,
+	EntityKind::Glider,
+	EntityKind::Teeth,
+	EntityKind::Walker,
+	EntityKind::Blob,
+	EntityKind::Paramecium,
+];
+
 #[derive(Debug)]
 pub struct EntityFuncs {
 	pub think: fn(&mut GameState, &mut Entity),
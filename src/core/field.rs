@@ -11,6 +11,7 @@ pub struct Field {
 	pub height: i32,
 	pub terrain: Vec<Terrain>,
 	pub conns: Vec<Connection>,
+	pub defs: TerrainDefs,
 }
 
 impl Field {
@@ -47,10 +48,20 @@ pub struct CanMoveFlags {
 	pub dirt: bool,
 	pub exit: bool,
 }
+impl CanMoveFlags {
+	pub fn allows(&self, hazard: TerrainHazard) -> bool {
+		match hazard {
+			TerrainHazard::Gravel => self.gravel,
+			TerrainHazard::Fire => self.fire,
+			TerrainHazard::Dirt => self.dirt,
+			TerrainHazard::Exit => self.exit,
+		}
+	}
+}
 impl Field {
 	pub fn can_move(&self, pos: Vec2i, dir: Dir, flags: &CanMoveFlags) -> bool {
 		let cur_terrain = self.get_terrain(pos);
-		let cur_solid = cur_terrain.solid_flags();
+		let cur_solid = self.defs.get(cur_terrain).solid;
 
 		// Allow movement if the terrain is solid
 		if cur_solid == SOLID_WALL {
@@ -69,7 +80,7 @@ impl Field {
 		}
 
 		let next_terrain = self.get_terrain(pos + dir.to_vec());
-		let next_solid = next_terrain.solid_flags();
+		let next_solid = self.defs.get(next_terrain).solid;
 
 		// Check the solid flags of the next terrain
 		let panel = match dir {
@@ -82,17 +93,11 @@ impl Field {
 			return false;
 		}
 
-		if !flags.gravel && matches!(next_terrain, Terrain::Gravel) {
-			return false;
-		}
-		if !flags.fire && matches!(next_terrain, Terrain::Fire) {
-			return false;
-		}
-		if !flags.dirt && matches!(next_terrain, Terrain::Dirt) {
-			return false;
-		}
-		if !flags.exit && matches!(next_terrain, Terrain::Exit) {
-			return false;
+		// Check the hazard category of the next terrain
+		if let Some(hazard) = self.defs.get(next_terrain).hazard {
+			if !flags.allows(hazard) {
+				return false;
+			}
 		}
 
 		return true;
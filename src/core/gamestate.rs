@@ -23,10 +23,18 @@ pub struct GameState {
 }
 
 impl GameState {
-	pub fn load(&mut self, json: &str) {
+	/// Loads a level in either the JSON or the text level format.
+	pub fn load(&mut self, level: &str) {
+		let ld = dto::LevelDto::parse(level).unwrap();
+		self.load_dto(ld);
+	}
+	/// Loads a level replacing the current level.
+	pub fn load_dto(&mut self, ld: dto::LevelDto) {
 		self.time = 0;
+		self.ps = PlayerState::default();
+		self.ents = EntityMap::default();
+		self.events.clear();
 
-		let ld: dto::LevelDto = serde_json::from_str(json).unwrap();
 		self.field.name = ld.name;
 		self.field.hint = ld.hint;
 		self.field.password = ld.password;
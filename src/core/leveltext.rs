@@ -0,0 +1,456 @@
+/*!
+Human-readable text level format.
+
+```text
+version: 1
+name: Lesson 1
+hint: Collect chips to get past the chip socket.
+password: BDHP
+seed: 1
+time: 100
+chips: 11
+size: 17x16
+
+[legend]
+_ Blank
+. Floor
+# Wall
+
+[map]
+__###__
+...
+
+[entities]
+. . @v. c .
+...
+
+[connections]
+3,4 -> 10,12
+```
+
+The map has one character per tile, each character is declared in the legend.
+The entity layer has two characters per tile: the entity kind and its facing marker (`^`, `<`, `v`, `>` or a space).
+Empty tiles are written as `. `, trailing whitespace may be omitted.
+Entities stacked on the same tile are written in additional `[entities]` layers.
+
+Entities are created in layer order then reading order when the level is loaded.
+If the level creates them in a different order, the `[order]` section lists for every entity in creation order its index in layer and reading order.
+A level without map data (every tile is floor) is written without a `[map]` section.
+*/
+
+use super::*;
+use super::dto::{LevelDto, MapDto, MapEncoding, LEVEL_VERSION};
+
+const ENTITY_CHARS: [(EntityKind, char); 23] = [
+	(EntityKind::Player, '@'),
+	(EntityKind::Chip, 'c'),
+	(EntityKind::Socket, 's'),
+	(EntityKind::Block, '#'),
+	(EntityKind::Flippers, 'w'),
+	(EntityKind::FireBoots, 'f'),
+	(EntityKind::IceSkates, 'i'),
+	(EntityKind::SuctionBoots, 'u'),
+	(EntityKind::BlueKey, 'b'),
+	(EntityKind::RedKey, 'r'),
+	(EntityKind::GreenKey, 'g'),
+	(EntityKind::YellowKey, 'y'),
+	(EntityKind::Thief, 't'),
+	(EntityKind::Bomb, 'o'),
+	(EntityKind::Bug, 'B'),
+	(EntityKind::FireBall, 'F'),
+	(EntityKind::PinkBall, 'P'),
+	(EntityKind::Tank, 'T'),
+	(EntityKind::Glider, 'G'),
+	(EntityKind::Teeth, 'M'),
+	(EntityKind::Walker, 'W'),
+	(EntityKind::Blob, 'O'),
+	(EntityKind::Paramecium, 'C'),
+];
+
+const TERRAIN_CHARS: [(Terrain, char); TERRAIN_COUNT] = [
+	(Terrain::Blank, '_'),
+	(Terrain::Floor, '.'),
+	(Terrain::Wall, '#'),
+	(Terrain::Socket, 'S'),
+	(Terrain::BlueLock, 'B'),
+	(Terrain::RedLock, 'R'),
+	(Terrain::GreenLock, 'G'),
+	(Terrain::YellowLock, 'Y'),
+	(Terrain::Hint, '?'),
+	(Terrain::Exit, 'E'),
+	(Terrain::Water, '~'),
+	(Terrain::Fire, 'F'),
+	(Terrain::Dirt, 'd'),
+	(Terrain::Gravel, ':'),
+	(Terrain::Ice, 'I'),
+	(Terrain::IceNW, '1'),
+	(Terrain::IceNE, '2'),
+	(Terrain::IceSW, '3'),
+	(Terrain::IceSE, '4'),
+	(Terrain::ForceN, '^'),
+	(Terrain::ForceW, '<'),
+	(Terrain::ForceS, 'v'),
+	(Terrain::ForceE, '>'),
+	(Terrain::ForceRandom, '*'),
+	(Terrain::CloneMachine, 'C'),
+	(Terrain::ToggleFloor, '-'),
+	(Terrain::ToggleWall, '='),
+	(Terrain::PanelN, 'n'),
+	(Terrain::PanelW, 'w'),
+	(Terrain::PanelS, 's'),
+	(Terrain::PanelE, 'e'),
+	(Terrain::PanelSE, 'j'),
+	(Terrain::HiddenWall, 'h'),
+	(Terrain::HiddenWallRevealed, 'H'),
+	(Terrain::InvisWall, 'i'),
+	(Terrain::BlueWall, 'X'),
+	(Terrain::BlueFake, 'x'),
+	(Terrain::GreenButton, 'g'),
+	(Terrain::RedButton, 'r'),
+	(Terrain::BrownButton, 'o'),
+	(Terrain::BlueButton, 'u'),
+	(Terrain::Teleport, 'T'),
+	(Terrain::BearTrap, 'O'),
+	(Terrain::RecessedWall, 'm'),
+	(Terrain::RaisedWall, 'M'),
+];
+
+/// Characters tried in order when the preferred legend character is already taken.
+const FALLBACK_CHARS: &str = "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ!\"$%&'()+,/;=@\\`{|}";
+
+fn is_legend_char(chr: char) -> bool {
+	chr.is_ascii_graphic() && chr != '['
+}
+
+fn facing_char(dir: Option<Dir>) -> char {
+	match dir {
+		Some(Dir::Up) => '^',
+		Some(Dir::Left) => '<',
+		Some(Dir::Down) => 'v',
+		Some(Dir::Right) => '>',
+		None => ' ',
+	}
+}
+
+fn facing_from_char(chr: char) -> Option<Option<Dir>> {
+	match chr {
+		'^' => Some(Some(Dir::Up)),
+		'<' => Some(Some(Dir::Left)),
+		'v' => Some(Some(Dir::Down)),
+		'>' => Some(Some(Dir::Right)),
+		' ' => Some(None),
+		_ => None,
+	}
+}
+
+fn escape(s: &str) -> String {
+	s.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(s: &str) -> String {
+	let mut result = String::with_capacity(s.len());
+	let mut chars = s.chars();
+	while let Some(chr) = chars.next() {
+		if chr != '\\' {
+			result.push(chr);
+			continue;
+		}
+		match chars.next() {
+			Some('n') => result.push('\n'),
+			Some(chr) => result.push(chr),
+			None => result.push('\\'),
+		}
+	}
+	result
+}
+
+/// Formats the level in the text level format.
+pub fn format(ld: &LevelDto) -> Result<String, String> {
+	use std::fmt::Write;
+
+	let mut s = String::new();
+	let _ = writeln!(s, "version: {}", LEVEL_VERSION);
+	let _ = writeln!(s, "name: {}", escape(&ld.name));
+	let _ = writeln!(s, "hint: {}", escape(&ld.hint));
+	let _ = writeln!(s, "password: {}", escape(&ld.password));
+	let _ = writeln!(s, "seed: {}", ld.seed);
+	let _ = writeln!(s, "time: {}", ld.time);
+	let _ = writeln!(s, "chips: {}", ld.chips);
+	let _ = writeln!(s, "size: {}x{}", ld.map.width, ld.map.height);
+
+	let width = ld.map.width as usize;
+	let height = ld.map.height as usize;
+
+	// Assign a character to every legend entry
+	let mut legend_chars = Vec::with_capacity(ld.map.legend.len());
+	for &terrain in &ld.map.legend {
+		let preferred = TERRAIN_CHARS[terrain as usize].1;
+		let chr = if !legend_chars.contains(&preferred) { Some(preferred) }
+		else { FALLBACK_CHARS.chars().find(|chr| !legend_chars.contains(chr)) };
+		let Some(chr) = chr else {
+			return Err(String::from("Too many legend entries"));
+		};
+		legend_chars.push(chr);
+	}
+
+	let _ = writeln!(s, "\n[legend]");
+	for (&terrain, &chr) in ld.map.legend.iter().zip(legend_chars.iter()) {
+		let _ = writeln!(s, "{} {:?}", chr, terrain);
+	}
+
+	if !ld.map.data.is_empty() {
+		if ld.map.data.len() != width * height {
+			return Err(String::from("Invalid map data length"));
+		}
+		let _ = writeln!(s, "\n[map]");
+		for row in ld.map.data.chunks(width) {
+			for &index in row {
+				let Some(&chr) = legend_chars.get(index as usize) else {
+					return Err(format!("Invalid legend index: {}", index));
+				};
+				s.push(chr);
+			}
+			s.push('\n');
+		}
+	}
+
+	// Place every entity in the first layer where its tile is free
+	let mut layers: Vec<Vec<Option<usize>>> = Vec::new();
+	for (ent_index, ent) in ld.entities.iter().enumerate() {
+		if ent.pos.x < 0 || ent.pos.y < 0 || ent.pos.x >= ld.map.width || ent.pos.y >= ld.map.height {
+			return Err(format!("Entity {:?} out of bounds at {}", ent.kind, ent.pos));
+		}
+		let index = ent.pos.y as usize * width + ent.pos.x as usize;
+		match layers.iter_mut().find(|layer| layer[index].is_none()) {
+			Some(layer) => layer[index] = Some(ent_index),
+			None => {
+				let mut layer = vec![None; width * height];
+				layer[index] = Some(ent_index);
+				layers.push(layer);
+			}
+		}
+	}
+
+	for layer in &layers {
+		let _ = writeln!(s, "\n[entities]");
+		for row in layer.chunks(width) {
+			let mut line = String::with_capacity(width * 2);
+			for &ent_index in row {
+				match ent_index {
+					Some(ent_index) => {
+						let ent = &ld.entities[ent_index];
+						line.push(ENTITY_CHARS[ent.kind as usize].1);
+						line.push(facing_char(ent.face_dir));
+					}
+					None => line.push_str(". "),
+				}
+			}
+			let _ = writeln!(s, "{}", line.trim_end());
+		}
+	}
+
+	// Index of every entity in layer and reading order
+	let mut read_order = vec![0; ld.entities.len()];
+	for (read_index, ent_index) in layers.iter().flatten().flatten().enumerate() {
+		read_order[*ent_index] = read_index;
+	}
+	if read_order.iter().enumerate().any(|(ent_index, &read_index)| ent_index != read_index) {
+		let _ = writeln!(s, "\n[order]");
+		for chunk in read_order.chunks(20) {
+			let line = chunk.iter().map(|index| index.to_string()).collect::<Vec<_>>().join(" ");
+			let _ = writeln!(s, "{}", line);
+		}
+	}
+
+	if !ld.connections.is_empty() {
+		let _ = writeln!(s, "\n[connections]");
+		for conn in &ld.connections {
+			let _ = writeln!(s, "{},{} -> {},{}", conn.src.x, conn.src.y, conn.dest.x, conn.dest.y);
+		}
+	}
+
+	Ok(s)
+}
+
+enum Section {
+	Header,
+	Legend,
+	Map,
+	Entities,
+	Order,
+	Connections,
+}
+
+fn parse_pos(s: &str) -> Option<Vec2i> {
+	let (x, y) = s.trim().split_once(',')?;
+	Some(Vec2i(x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+/// Parses a level in the text level format.
+pub fn parse(text: &str) -> Result<LevelDto, String> {
+	let mut ld = LevelDto {
+		version: LEVEL_VERSION,
+		name: String::new(),
+		hint: String::new(),
+		password: String::new(),
+		seed: 0,
+		time: 0,
+		chips: 0,
+		map: MapDto { width: 0, height: 0, data: Vec::new(), legend: Vec::new(), encoding: MapEncoding::Plain },
+		entities: Vec::new(),
+		connections: Vec::new(),
+	};
+
+	let mut legend_chars = Vec::new();
+	let mut map_rows = Vec::new();
+	let mut layers: Vec<Vec<&str>> = Vec::new();
+	let mut order: Option<Vec<usize>> = None;
+	let mut section = Section::Header;
+
+	for (line_index, line) in text.lines().enumerate() {
+		let lineno = line_index + 1;
+		let line = line.trim_end_matches('\r');
+
+		if line.trim().is_empty() {
+			continue;
+		}
+		if line.starts_with('[') {
+			section = match line.trim_end() {
+				"[legend]" => Section::Legend,
+				"[map]" => Section::Map,
+				"[entities]" => {
+					layers.push(Vec::new());
+					Section::Entities
+				}
+				"[order]" => Section::Order,
+				"[connections]" => Section::Connections,
+				_ => return Err(format!("Line {}: Unknown section: {}", lineno, line)),
+			};
+			continue;
+		}
+
+		match section {
+			Section::Header => {
+				let Some((key, value)) = line.split_once(':') else {
+					return Err(format!("Line {}: Expected key: value", lineno));
+				};
+				let value = value.strip_prefix(' ').unwrap_or(value);
+				let invalid = || format!("Line {}: Invalid {}: {}", lineno, key, value);
+				match key {
+					"version" => {
+						let version: u32 = value.trim().parse().map_err(|_| invalid())?;
+						if version > LEVEL_VERSION {
+							return Err(format!("Line {}: Unsupported level version: {}", lineno, version));
+						}
+					}
+					"name" => ld.name = unescape(value),
+					"hint" => ld.hint = unescape(value),
+					"password" => ld.password = unescape(value),
+					"seed" => ld.seed = value.trim().parse().map_err(|_| invalid())?,
+					"time" => ld.time = value.trim().parse().map_err(|_| invalid())?,
+					"chips" => ld.chips = value.trim().parse().map_err(|_| invalid())?,
+					"size" => {
+						let (width, height) = value.trim().split_once('x').ok_or_else(invalid)?;
+						ld.map.width = width.parse().map_err(|_| invalid())?;
+						ld.map.height = height.parse().map_err(|_| invalid())?;
+					}
+					_ => return Err(format!("Line {}: Unknown key: {}", lineno, key)),
+				}
+			}
+			Section::Legend => {
+				let mut chars = line.chars();
+				let chr = chars.next().unwrap();
+				let name = chars.as_str().trim();
+				if !is_legend_char(chr) {
+					return Err(format!("Line {}: Invalid legend character: {:?}", lineno, chr));
+				}
+				if legend_chars.contains(&chr) {
+					return Err(format!("Line {}: Duplicate legend character: {:?}", lineno, chr));
+				}
+				let Some(&terrain) = ALL_TERRAIN.iter().find(|terrain| format!("{:?}", terrain) == name) else {
+					return Err(format!("Line {}: Unknown terrain: {}", lineno, name));
+				};
+				legend_chars.push(chr);
+				ld.map.legend.push(terrain);
+			}
+			Section::Map => map_rows.push((lineno, line)),
+			Section::Entities => layers.last_mut().unwrap().push(line),
+			Section::Order => {
+				let order = order.get_or_insert_with(Vec::new);
+				for index in line.split_whitespace() {
+					let Ok(index) = index.parse() else {
+						return Err(format!("Line {}: Invalid entity index: {}", lineno, index));
+					};
+					order.push(index);
+				}
+			}
+			Section::Connections => {
+				let conn = line.split_once("->").and_then(|(src, dest)| Some(Connection { src: parse_pos(src)?, dest: parse_pos(dest)? }));
+				let Some(conn) = conn else {
+					return Err(format!("Line {}: Invalid connection: {}", lineno, line));
+				};
+				ld.connections.push(conn);
+			}
+		}
+	}
+
+	let width = ld.map.width;
+	let height = ld.map.height;
+	if width <= 0 || height <= 0 {
+		return Err(String::from("Invalid map size"));
+	}
+
+	if !map_rows.is_empty() {
+		if map_rows.len() != height as usize {
+			return Err(format!("Expected {} map rows, found {}", height, map_rows.len()));
+		}
+		ld.map.data.reserve_exact(width as usize * height as usize);
+		for &(lineno, row) in &map_rows {
+			if row.chars().count() != width as usize {
+				return Err(format!("Line {}: Expected {} map columns", lineno, width));
+			}
+			for chr in row.chars() {
+				let Some(index) = legend_chars.iter().position(|&c| c == chr) else {
+					return Err(format!("Line {}: Character not in legend: {:?}", lineno, chr));
+				};
+				ld.map.data.push(index as u8);
+			}
+		}
+	}
+
+	for layer in &layers {
+		if layer.len() != height as usize {
+			return Err(format!("Expected {} entity rows, found {}", height, layer.len()));
+		}
+		for (y, row) in layer.iter().enumerate() {
+			let chars = row.chars().collect::<Vec<char>>();
+			if chars.len() > width as usize * 2 {
+				return Err(format!("Entity row {} is too long", y));
+			}
+			for x in 0..width as usize {
+				let kind_chr = chars.get(x * 2).cloned().unwrap_or('.');
+				let face_chr = chars.get(x * 2 + 1).cloned().unwrap_or(' ');
+				if kind_chr == '.' {
+					continue;
+				}
+				let Some(&(kind, _)) = ENTITY_CHARS.iter().find(|&&(_, c)| c == kind_chr) else {
+					return Err(format!("Unknown entity {:?} at {},{}", kind_chr, x, y));
+				};
+				let Some(face_dir) = facing_from_char(face_chr) else {
+					return Err(format!("Unknown facing {:?} at {},{}", face_chr, x, y));
+				};
+				ld.entities.push(EntityArgs { kind, pos: Vec2i(x as i32, y as i32), face_dir });
+			}
+		}
+	}
+
+	if let Some(order) = order {
+		let mut used = vec![false; ld.entities.len()];
+		if order.len() != ld.entities.len() || !order.iter().all(|&index| index < used.len() && !std::mem::replace(&mut used[index], true)) {
+			return Err(format!("Entity order does not list each of the {} entities once", ld.entities.len()));
+		}
+		ld.entities = order.iter().map(|&index| ld.entities[index]).collect();
+	}
+
+	Ok(ld)
+}
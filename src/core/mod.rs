@@ -12,8 +12,10 @@ mod event;
 mod field;
 mod gamestate;
 mod inbuf;
+pub mod leveltext;
 mod playerstate;
 mod terrain;
+mod terraindef;
 mod quadtree;
 
 pub use self::connection::*;
@@ -27,6 +29,7 @@ pub use self::gamestate::*;
 pub use self::inbuf::*;
 pub use self::playerstate::*;
 pub use self::terrain::*;
+pub use self::terraindef::*;
 pub use self::quadtree::*;
 
 #[derive(Copy, Clone, Default)]
@@ -37,6 +40,8 @@ pub struct Input {
 	pub right: bool,
 	pub up: bool,
 	pub down: bool,
+	/// Toggles walking through walls when pressed.
+	pub dev_wtw: bool,
 }
 
 #[derive(Debug)]
@@ -52,3 +57,6 @@ pub const PANEL_S: u8 = 0x4;
 pub const PANEL_W: u8 = 0x8;
 
 type Time = i32;
+
+/// Number of game ticks per second.
+pub const TICK_RATE: f32 = 60.0;
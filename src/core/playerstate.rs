@@ -42,7 +42,7 @@ pub struct PlayerState {
 }
 
 pub fn ps_update_moves(s: &mut GameState, input: &Input) {
-	if !(s.input.a && s.input.b) && input.a && input.b {
+	if !s.input.dev_wtw && input.dev_wtw {
 		s.ps.dev_wtw = !s.ps.dev_wtw;
 	}
 	s.ps.inbuf.handle(Dir::Left,  input.left,  s.input.left);
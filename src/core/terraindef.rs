@@ -0,0 +1,78 @@
+use super::*;
+
+/// Movement hazard categories, see [`CanMoveFlags`].
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum TerrainHazard {
+	Gravel,
+	Fire,
+	Dirt,
+	Exit,
+}
+
+/// Terrain definition as loaded from the data file.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct TerrainDef {
+	pub terrain: Terrain,
+	/// Solid flags, combination of `PANEL_*` bits or `SOLID_WALL`.
+	pub solid: u8,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub hazard: Option<TerrainHazard>,
+}
+
+#[derive(serde::Deserialize)]
+struct DataDto {
+	terrain: Vec<TerrainDef>,
+}
+
+/// Terrain definitions table with an entry for every terrain.
+#[derive(Clone, Debug)]
+pub struct TerrainDefs {
+	defs: [TerrainDef; TERRAIN_COUNT],
+}
+
+static BUILTIN_DEFS: std::sync::OnceLock<TerrainDefs> = std::sync::OnceLock::new();
+
+impl Default for TerrainDefs {
+	fn default() -> Self {
+		BUILTIN_DEFS.get_or_init(|| TerrainDefs::parse(include_str!("../../data/data.json")).unwrap()).clone()
+	}
+}
+
+impl TerrainDefs {
+	/// Parses the `terrain` section of the data file.
+	pub fn parse(json: &str) -> Result<TerrainDefs, String> {
+		let dto: DataDto = serde_json::from_str(json).map_err(|err| err.to_string())?;
+		TerrainDefs::from_defs(&dto.terrain)
+	}
+
+	/// Validates the definitions, every terrain must be defined exactly once.
+	pub fn from_defs(list: &[TerrainDef]) -> Result<TerrainDefs, String> {
+		let mut slots = [None; TERRAIN_COUNT];
+		for def in list {
+			if def.solid & !SOLID_WALL != 0 {
+				return Err(format!("Invalid solid flags for {:?}: {}", def.terrain, def.solid));
+			}
+			let slot = &mut slots[def.terrain as usize];
+			if slot.is_some() {
+				return Err(format!("Duplicate terrain definition: {:?}", def.terrain));
+			}
+			*slot = Some(*def);
+		}
+
+		let mut defs = [TerrainDef::default(); TERRAIN_COUNT];
+		for &terrain in ALL_TERRAIN.iter() {
+			let Some(def) = slots[terrain as usize] else {
+				return Err(format!("Missing terrain definition: {:?}", terrain));
+			};
+			defs[terrain as usize] = def;
+		}
+		Ok(TerrainDefs { defs })
+	}
+
+	#[inline]
+	pub fn get(&self, terrain: Terrain) -> &TerrainDef {
+		&self.defs[terrain as usize]
+	}
+}
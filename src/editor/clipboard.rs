@@ -0,0 +1,156 @@
+use super::*;
+
+/// Rectangular piece of a level with its entities and connections.
+///
+/// Positions are relative to the top left corner of the piece.
+#[derive(Clone, Debug)]
+pub struct Clipboard {
+	pub size: Vec2<i32>,
+	pub terrain: Vec<core::Terrain>,
+	pub entities: Vec<core::EntityArgs>,
+	/// Connections with both ends inside the piece.
+	pub conns: Vec<core::Connection>,
+}
+
+impl Clipboard {
+	/// Copies the tiles between the inclusive bounds.
+	pub fn copy(map: &MapState, min: Vec2<i32>, max: Vec2<i32>) -> Clipboard {
+		let size = max - min + Vec2(1, 1);
+		let inside = |pos: Vec2<i32>| pos.x >= min.x && pos.y >= min.y && pos.x <= max.x && pos.y <= max.y;
+
+		let mut terrain = Vec::with_capacity(size.x as usize * size.y as usize);
+		for y in min.y..=max.y {
+			for x in min.x..=max.x {
+				terrain.push(map.get_terrain(Vec2(x, y)));
+			}
+		}
+
+		let entities = map.entities.iter()
+			.filter(|args| inside(args.pos))
+			.map(|&args| core::EntityArgs { pos: args.pos - min, ..args })
+			.collect();
+
+		let conns = map.conns.iter()
+			.filter(|conn| inside(conn.src) && inside(conn.dest))
+			.map(|conn| core::Connection { src: conn.src - min, dest: conn.dest - min })
+			.collect();
+
+		Clipboard { size, terrain, entities, conns }
+	}
+
+	pub fn get_terrain(&self, pos: Vec2<i32>) -> core::Terrain {
+		self.terrain[(pos.y * self.size.x + pos.x) as usize]
+	}
+
+	/// Rotates the piece clockwise by 90 degrees.
+	///
+	/// Fails and leaves the piece unchanged if it contains terrain without a rotated counterpart.
+	pub fn rotate(&mut self) -> Result<(), String> {
+		let size = Vec2(self.size.y, self.size.x);
+		let rotate_pos = |pos: Vec2<i32>| Vec2(self.size.y - 1 - pos.y, pos.x);
+
+		let mut terrain = vec![core::Terrain::Blank; self.terrain.len()];
+		for y in 0..self.size.y {
+			for x in 0..self.size.x {
+				let pos = rotate_pos(Vec2(x, y));
+				let tile = self.get_terrain(Vec2(x, y));
+				let Some(rotated) = rotate_terrain(tile) else {
+					return Err(format!("Cannot rotate {:?} at {}", tile, Vec2(x, y)));
+				};
+				terrain[(pos.y * size.x + pos.x) as usize] = rotated;
+			}
+		}
+
+		for ent in &mut self.entities {
+			ent.pos = rotate_pos(ent.pos);
+			ent.face_dir = ent.face_dir.map(core::Dir::turn_right);
+		}
+		for conn in &mut self.conns {
+			conn.src = rotate_pos(conn.src);
+			conn.dest = rotate_pos(conn.dest);
+		}
+
+		self.size = size;
+		self.terrain = terrain;
+		Ok(())
+	}
+
+	/// Mirrors the piece horizontally.
+	///
+	/// Fails and leaves the piece unchanged if it contains terrain without a mirrored counterpart.
+	pub fn mirror(&mut self) -> Result<(), String> {
+		let mirror_pos = |pos: Vec2<i32>| Vec2(self.size.x - 1 - pos.x, pos.y);
+
+		let mut terrain = self.terrain.clone();
+		for y in 0..self.size.y {
+			for x in 0..self.size.x {
+				let pos = mirror_pos(Vec2(x, y));
+				let tile = self.get_terrain(Vec2(x, y));
+				let Some(mirrored) = mirror_terrain(tile) else {
+					return Err(format!("Cannot mirror {:?} at {}", tile, Vec2(x, y)));
+				};
+				terrain[(pos.y * self.size.x + pos.x) as usize] = mirrored;
+			}
+		}
+
+		for ent in &mut self.entities {
+			ent.pos = mirror_pos(ent.pos);
+			ent.face_dir = ent.face_dir.map(|dir| match dir {
+				core::Dir::Left => core::Dir::Right,
+				core::Dir::Right => core::Dir::Left,
+				dir => dir,
+			});
+		}
+		for conn in &mut self.conns {
+			conn.src = mirror_pos(conn.src);
+			conn.dest = mirror_pos(conn.dest);
+		}
+
+		self.terrain = terrain;
+		Ok(())
+	}
+}
+
+/// Returns the terrain rotated clockwise by 90 degrees.
+///
+/// Returns none for the south-east panel as there is no south-west panel.
+pub fn rotate_terrain(terrain: core::Terrain) -> Option<core::Terrain> {
+	use crate::core::Terrain::*;
+	let rotated = match terrain {
+		IceNW => IceNE,
+		IceNE => IceSE,
+		IceSE => IceSW,
+		IceSW => IceNW,
+		ForceN => ForceE,
+		ForceE => ForceS,
+		ForceS => ForceW,
+		ForceW => ForceN,
+		PanelN => PanelE,
+		PanelE => PanelS,
+		PanelS => PanelW,
+		PanelW => PanelN,
+		PanelSE => return None,
+		terrain => terrain,
+	};
+	Some(rotated)
+}
+
+/// Returns the terrain mirrored horizontally.
+///
+/// Returns none for the south-east panel as there is no south-west panel.
+pub fn mirror_terrain(terrain: core::Terrain) -> Option<core::Terrain> {
+	use crate::core::Terrain::*;
+	let mirrored = match terrain {
+		IceNW => IceNE,
+		IceNE => IceNW,
+		IceSW => IceSE,
+		IceSE => IceSW,
+		ForceW => ForceE,
+		ForceE => ForceW,
+		PanelW => PanelE,
+		PanelE => PanelW,
+		PanelSE => return None,
+		terrain => terrain,
+	};
+	Some(mirrored)
+}
@@ -0,0 +1,455 @@
+use super::*;
+
+/// Level properties edited as a whole.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LevelProps {
+	pub name: String,
+	pub hint: String,
+	pub password: String,
+	/// Seed of the level's random number generator.
+	pub seed: u64,
+	pub time: i32,
+	pub chips: i32,
+}
+
+/// Map dimensions with everything placed on the map.
+#[derive(Clone, Debug, Default)]
+pub struct MapState {
+	pub width: i32,
+	pub height: i32,
+	pub terrain: Vec<core::Terrain>,
+	/// Entities in the order they are created when the level is loaded.
+	pub entities: Vec<core::EntityArgs>,
+	pub conns: Vec<core::Connection>,
+}
+
+impl MapState {
+	#[inline]
+	pub fn in_bounds(&self, pos: Vec2<i32>) -> bool {
+		pos.x >= 0 && pos.y >= 0 && pos.x < self.width && pos.y < self.height
+	}
+	pub fn get_terrain(&self, pos: Vec2<i32>) -> core::Terrain {
+		if !self.in_bounds(pos) {
+			return core::Terrain::Blank;
+		}
+		self.terrain[(pos.y * self.width + pos.x) as usize]
+	}
+	/// Checks the terrain at both ends of the connection.
+	pub fn conn_kind(&self, conn: &core::Connection) -> Result<core::ConnKind, String> {
+		core::ConnKind::validate(self.get_terrain(conn.src), self.get_terrain(conn.dest))
+	}
+}
+
+/// Level being edited, independent of how it is presented.
+///
+/// Every edit is recorded in the history and can be undone.
+pub struct EditorDocument {
+	props: LevelProps,
+	map: MapState,
+	/// Legend of the loaded level, saving keeps its order so unchanged levels save identically.
+	legend: Vec<core::Terrain>,
+	encoding: core::dto::MapEncoding,
+	history: History,
+	clipboard: Option<Clipboard>,
+	revision: u64,
+	/// Commands applied since the changes were last taken.
+	changes: Vec<Command>,
+}
+
+impl Default for EditorDocument {
+	fn default() -> Self {
+		EditorDocument::new(32, 32)
+	}
+}
+
+impl EditorDocument {
+	/// Creates an empty level filled with floor.
+	pub fn new(width: i32, height: i32) -> EditorDocument {
+		let map = MapState {
+			width,
+			height,
+			terrain: vec![core::Terrain::Floor; width as usize * height as usize],
+			entities: Vec::new(),
+			conns: Vec::new(),
+		};
+		let props = LevelProps { seed: urandom::new().next_u64(), ..LevelProps::default() };
+		let legend = vec![core::Terrain::Blank, core::Terrain::Floor];
+		EditorDocument { props, map, legend, encoding: core::dto::MapEncoding::Plain, history: History::default(), clipboard: None, revision: 0, changes: Vec::new() }
+	}
+
+	/// Parses a level in either the JSON or the text level format.
+	pub fn parse(s: &str) -> Result<EditorDocument, String> {
+		EditorDocument::from_dto(core::dto::LevelDto::parse(s)?)
+	}
+
+	pub fn from_dto(ld: core::dto::LevelDto) -> Result<EditorDocument, String> {
+		if ld.map.width <= 0 || ld.map.height <= 0 {
+			return Err(String::from("Invalid map size"));
+		}
+		let size = ld.map.width as usize * ld.map.height as usize;
+		let terrain = if ld.map.data.is_empty() {
+			vec![core::Terrain::Floor; size]
+		}
+		else {
+			if ld.map.data.len() != size {
+				return Err(String::from("Invalid map data length"));
+			}
+			ld.map.data.iter().map(|&index| ld.map.legend.get(index as usize).cloned().ok_or_else(|| format!("Invalid legend index: {}", index))).collect::<Result<_, _>>()?
+		};
+
+		let props = LevelProps {
+			name: ld.name,
+			hint: ld.hint,
+			password: ld.password,
+			seed: ld.seed,
+			time: ld.time,
+			chips: ld.chips,
+		};
+		let map = MapState {
+			width: ld.map.width,
+			height: ld.map.height,
+			terrain,
+			entities: ld.entities,
+			conns: ld.connections,
+		};
+		Ok(EditorDocument { props, map, legend: ld.map.legend, encoding: ld.map.encoding, history: History::default(), clipboard: None, revision: 0, changes: Vec::new() })
+	}
+
+	pub fn to_dto(&self) -> core::dto::LevelDto {
+		// Terrain missing from the legend is appended in map order
+		let mut legend = self.legend.clone();
+		let mut legend_map = HashMap::new();
+		for (idx, &terrain) in legend.iter().enumerate() {
+			legend_map.entry(terrain).or_insert(idx as u8);
+		}
+		for &terrain in self.map.terrain.iter() {
+			if let std::collections::hash_map::Entry::Vacant(entry) = legend_map.entry(terrain) {
+				entry.insert(legend.len() as u8);
+				legend.push(terrain);
+			}
+		}
+		let data = self.map.terrain.iter().map(|&terrain| legend_map[&terrain]).collect();
+
+		core::dto::LevelDto {
+			version: core::dto::LEVEL_VERSION,
+			name: self.props.name.clone(),
+			hint: self.props.hint.clone(),
+			password: self.props.password.clone(),
+			seed: self.props.seed,
+			time: self.props.time,
+			chips: self.props.chips,
+			map: core::dto::MapDto {
+				width: self.map.width,
+				height: self.map.height,
+				data,
+				legend,
+				encoding: self.encoding,
+			},
+			// Entities are created in this order which must be kept for the level to play the same
+			entities: self.map.entities.clone(),
+			connections: self.map.conns.clone(),
+		}
+	}
+
+	/// Saves the level as pretty printed JSON.
+	///
+	/// Saving an unchanged level produces the same output every time.
+	pub fn save(&self) -> String {
+		serde_json::to_string_pretty(&self.to_dto()).unwrap()
+	}
+
+	/// Incremented on every change to the level.
+	pub fn revision(&self) -> u64 {
+		self.revision
+	}
+	/// Takes the commands applied since the last call in the order they were applied, eg. to update a view.
+	///
+	/// Undoing a command applies its inverse.
+	pub fn take_changes(&mut self) -> Vec<Command> {
+		std::mem::take(&mut self.changes)
+	}
+	pub fn map(&self) -> &MapState {
+		&self.map
+	}
+	pub fn properties(&self) -> &LevelProps {
+		&self.props
+	}
+	pub fn clipboard(&self) -> Option<&Clipboard> {
+		self.clipboard.as_ref()
+	}
+
+	//----------------------------------------------------------------
+	// History
+
+	/// Groups the following edits into a single undo step, eg. a mouse stroke.
+	pub fn begin(&mut self) {
+		self.history.begin();
+	}
+	pub fn end(&mut self) {
+		self.history.end();
+	}
+	/// Sets the maximum number of edits that can be undone.
+	pub fn set_history_depth(&mut self, depth: usize) {
+		self.history.set_depth(depth);
+	}
+	pub fn undo(&mut self) -> bool {
+		let Some(group) = self.history.undo() else { return false };
+		let group = group.to_vec();
+		for cmd in group.iter().rev() {
+			self.apply(&cmd.inverse());
+		}
+		true
+	}
+	pub fn redo(&mut self) -> bool {
+		let Some(group) = self.history.redo() else { return false };
+		let group = group.to_vec();
+		for cmd in &group {
+			self.apply(cmd);
+		}
+		true
+	}
+
+	/// Applies the command and records it in the history.
+	fn exec(&mut self, cmd: Command) {
+		self.apply(&cmd);
+		self.history.push(cmd);
+	}
+	fn apply(&mut self, cmd: &Command) {
+		self.revision += 1;
+		self.changes.push(cmd.clone());
+		match cmd {
+			&Command::SetTerrain { pos, new, .. } => {
+				if self.map.in_bounds(pos) {
+					self.map.terrain[(pos.y * self.map.width + pos.x) as usize] = new;
+				}
+			}
+			&Command::PlaceEntity { index, args } => {
+				self.map.entities.insert(usize::min(index, self.map.entities.len()), args);
+			}
+			&Command::RemoveEntity { index, .. } => {
+				if index < self.map.entities.len() {
+					self.map.entities.remove(index);
+				}
+			}
+			&Command::AddConnection { index, conn } => {
+				self.map.conns.insert(usize::min(index, self.map.conns.len()), conn);
+			}
+			&Command::RemoveConnection { index, .. } => {
+				if index < self.map.conns.len() {
+					self.map.conns.remove(index);
+				}
+			}
+			Command::SetProperties { new, .. } => {
+				self.props = new.clone();
+			}
+			Command::SetMap { new, .. } => {
+				self.map = new.clone();
+			}
+		}
+	}
+
+	//----------------------------------------------------------------
+	// Properties
+
+	pub fn set_properties(&mut self, props: LevelProps) {
+		if self.props != props {
+			self.exec(Command::SetProperties { old: self.props.clone(), new: props });
+		}
+	}
+	/// Edits the level properties as a single undoable change.
+	pub fn edit_properties<F: FnOnce(&mut LevelProps)>(&mut self, f: F) {
+		let mut props = self.props.clone();
+		f(&mut props);
+		self.set_properties(props);
+	}
+	/// Counts the chips placed on the map.
+	pub fn count_chips(&self) -> i32 {
+		self.map.entities.iter().filter(|args| args.kind == core::EntityKind::Chip).count() as i32
+	}
+	/// Sets the number of chips required to the number of chips placed on the map.
+	pub fn update_chips(&mut self) {
+		let chips = self.count_chips();
+		self.edit_properties(|props| props.chips = chips);
+	}
+	/// Assigns a random password not used by any of the given passwords.
+	pub fn generate_password(&mut self, taken: &[String]) {
+		let password = random_password(taken);
+		self.edit_properties(|props| props.password = password);
+	}
+	/// Picks a new random seed for the level.
+	pub fn reseed(&mut self) {
+		let seed = urandom::new().next_u64();
+		self.edit_properties(|props| props.seed = seed);
+	}
+
+	//----------------------------------------------------------------
+	// Editing
+
+	pub fn set_terrain(&mut self, pos: Vec2<i32>, terrain: core::Terrain) {
+		if !self.map.in_bounds(pos) {
+			return;
+		}
+		let old = self.map.get_terrain(pos);
+		if old != terrain {
+			self.exec(Command::SetTerrain { pos, old, new: terrain });
+		}
+	}
+	pub fn place_entity(&mut self, args: core::EntityArgs) {
+		if self.map.in_bounds(args.pos) {
+			self.exec(Command::PlaceEntity { index: self.map.entities.len(), args });
+		}
+	}
+	/// Removes all entities on the tile.
+	pub fn erase(&mut self, pos: Vec2<i32>) {
+		self.begin();
+		while let Some(index) = self.map.entities.iter().rposition(|args| args.pos == pos) {
+			let args = self.map.entities[index];
+			self.exec(Command::RemoveEntity { index, args });
+		}
+		self.end();
+	}
+	/// Applies the tool to the tile.
+	pub fn apply_tool(&mut self, tool: Tool, pos: Vec2<i32>) {
+		match tool {
+			Tool::Terrain(terrain) => self.set_terrain(pos, terrain),
+			Tool::Entity(args) => self.place_entity(core::EntityArgs { pos, ..args }),
+			Tool::Erase => self.erase(pos),
+		}
+	}
+	/// Adds the connection or removes it if it already exists.
+	pub fn connect(&mut self, src: Vec2<i32>, dest: Vec2<i32>) {
+		if src == dest {
+			return;
+		}
+		let conn = core::Connection { src, dest };
+		if let Some(index) = self.map.conns.iter().position(|c| c == &conn) {
+			self.exec(Command::RemoveConnection { index, conn });
+		}
+		else {
+			self.exec(Command::AddConnection { index: self.map.conns.len(), conn });
+		}
+	}
+
+	/// Removes the connection at the index in the connection list.
+	pub fn remove_connection(&mut self, index: usize) -> bool {
+		let Some(&conn) = self.map.conns.get(index) else { return false };
+		self.exec(Command::RemoveConnection { index, conn });
+		true
+	}
+	/// Returns the indices of the connections which don't connect matching terrain.
+	pub fn invalid_connections(&self) -> Vec<usize> {
+		(0..self.map.conns.len()).filter(|&index| self.map.conn_kind(&self.map.conns[index]).is_err()).collect()
+	}
+
+	/// Grows (positive) or shrinks (negative) the map at each edge.
+	///
+	/// Entities and connections move along with the terrain, those which end up outside the map are removed.
+	/// New tiles are filled with floor.
+	pub fn resize(&mut self, left: i32, top: i32, right: i32, bottom: i32) -> bool {
+		let old = &self.map;
+		let width = old.width + left + right;
+		let height = old.height + top + bottom;
+		if width <= 0 || height <= 0 {
+			return false;
+		}
+		let offset = Vec2(left, top);
+		let in_bounds = |pos: Vec2<i32>| pos.x >= 0 && pos.y >= 0 && pos.x < width && pos.y < height;
+
+		let mut terrain = Vec::with_capacity(width as usize * height as usize);
+		for y in 0..height {
+			for x in 0..width {
+				let pos = Vec2(x, y) - offset;
+				terrain.push(if old.in_bounds(pos) { old.get_terrain(pos) } else { core::Terrain::Floor });
+			}
+		}
+		let entities = old.entities.iter()
+			.map(|&args| core::EntityArgs { pos: args.pos + offset, ..args })
+			.filter(|args| in_bounds(args.pos))
+			.collect();
+		let conns = old.conns.iter()
+			.map(|conn| core::Connection { src: conn.src + offset, dest: conn.dest + offset })
+			.filter(|conn| in_bounds(conn.src) && in_bounds(conn.dest))
+			.collect();
+
+		let new = MapState { width, height, terrain, entities, conns };
+		self.exec(Command::SetMap { old: self.map.clone(), new });
+		true
+	}
+
+	//----------------------------------------------------------------
+	// Clipboard
+
+	/// Copies the tiles between the inclusive bounds to the clipboard.
+	pub fn copy(&mut self, min: Vec2<i32>, max: Vec2<i32>) {
+		self.clipboard = Some(Clipboard::copy(&self.map, min, max));
+	}
+	/// Copies the tiles between the inclusive bounds to the clipboard and clears them to floor.
+	pub fn cut(&mut self, min: Vec2<i32>, max: Vec2<i32>) {
+		self.copy(min, max);
+		let inside = |pos: Vec2<i32>| pos.x >= min.x && pos.y >= min.y && pos.x <= max.x && pos.y <= max.y;
+
+		self.begin();
+		for pos in shapes::rect(min, max) {
+			self.erase(pos);
+			self.set_terrain(pos, core::Terrain::Floor);
+		}
+		while let Some(index) = self.map.conns.iter().rposition(|conn| inside(conn.src) || inside(conn.dest)) {
+			let conn = self.map.conns[index];
+			self.exec(Command::RemoveConnection { index, conn });
+		}
+		self.end();
+	}
+	/// Pastes the clipboard with its top left corner at the origin.
+	///
+	/// Entities under the pasted tiles are replaced.
+	pub fn paste(&mut self, origin: Vec2<i32>) -> bool {
+		let Some(clip) = self.clipboard.clone() else { return false };
+
+		self.begin();
+		for y in 0..clip.size.y {
+			for x in 0..clip.size.x {
+				let pos = origin + Vec2(x, y);
+				if self.map.in_bounds(pos) {
+					self.erase(pos);
+					self.set_terrain(pos, clip.get_terrain(Vec2(x, y)));
+				}
+			}
+		}
+		for &args in &clip.entities {
+			self.place_entity(core::EntityArgs { pos: origin + args.pos, ..args });
+		}
+		for conn in &clip.conns {
+			let conn = core::Connection { src: origin + conn.src, dest: origin + conn.dest };
+			if self.map.in_bounds(conn.src) && self.map.in_bounds(conn.dest) && !self.map.conns.contains(&conn) {
+				self.exec(Command::AddConnection { index: self.map.conns.len(), conn });
+			}
+		}
+		self.end();
+		true
+	}
+	/// Rotates the clipboard clockwise by 90 degrees.
+	pub fn rotate_clipboard(&mut self) -> Result<(), String> {
+		match &mut self.clipboard {
+			Some(clip) => clip.rotate(),
+			None => Ok(()),
+		}
+	}
+	/// Mirrors the clipboard horizontally.
+	pub fn mirror_clipboard(&mut self) -> Result<(), String> {
+		match &mut self.clipboard {
+			Some(clip) => clip.mirror(),
+			None => Ok(()),
+		}
+	}
+}
+
+/// Generates a random four letter password not in the list of taken passwords.
+pub fn random_password(taken: &[String]) -> String {
+	let mut rng = urandom::new();
+	loop {
+		let password = (0..4).map(|_| *rng.choose(b"ABCDEFGHIJKLMNOPQRSTUVWXYZ").unwrap() as char).collect::<String>();
+		if !taken.contains(&password) {
+			return password;
+		}
+	}
+}
@@ -0,0 +1,150 @@
+use super::*;
+
+/// Reversible edit to the level.
+#[derive(Clone, Debug)]
+pub enum Command {
+	SetTerrain { pos: Vec2<i32>, old: core::Terrain, new: core::Terrain },
+	/// Inserts the entity at the index in the entity list.
+	PlaceEntity { index: usize, args: core::EntityArgs },
+	RemoveEntity { index: usize, args: core::EntityArgs },
+	/// Inserts the connection at the index in the connection list.
+	AddConnection { index: usize, conn: core::Connection },
+	RemoveConnection { index: usize, conn: core::Connection },
+	SetProperties { old: LevelProps, new: LevelProps },
+	/// Replaces the whole map, eg. when resizing.
+	SetMap { old: MapState, new: MapState },
+}
+
+impl Command {
+	/// Returns the command which reverts this command.
+	pub fn inverse(&self) -> Command {
+		match self {
+			&Command::SetTerrain { pos, old, new } => Command::SetTerrain { pos, old: new, new: old },
+			&Command::PlaceEntity { index, args } => Command::RemoveEntity { index, args },
+			&Command::RemoveEntity { index, args } => Command::PlaceEntity { index, args },
+			&Command::AddConnection { index, conn } => Command::RemoveConnection { index, conn },
+			&Command::RemoveConnection { index, conn } => Command::AddConnection { index, conn },
+			Command::SetProperties { old, new } => Command::SetProperties { old: new.clone(), new: old.clone() },
+			Command::SetMap { old, new } => Command::SetMap { old: new.clone(), new: old.clone() },
+		}
+	}
+}
+
+/// Undo and redo stacks of command groups.
+///
+/// Commands pushed between [`begin`](History::begin) and [`end`](History::end) form a single group, eg. one mouse stroke.
+/// Groups may be nested, the outermost group is recorded.
+pub struct History {
+	undo: std::collections::VecDeque<Vec<Command>>,
+	redo: Vec<Vec<Command>>,
+	group: Option<Vec<Command>>,
+	nesting: usize,
+	depth: usize,
+}
+
+impl Default for History {
+	fn default() -> Self {
+		History::new(100)
+	}
+}
+
+impl History {
+	pub fn new(depth: usize) -> History {
+		History {
+			undo: Default::default(),
+			redo: Vec::new(),
+			group: None,
+			nesting: 0,
+			depth,
+		}
+	}
+
+	/// Starts grouping commands.
+	pub fn begin(&mut self) {
+		if self.nesting == 0 {
+			self.group = Some(Vec::new());
+		}
+		self.nesting += 1;
+	}
+
+	/// Stops grouping commands.
+	pub fn end(&mut self) {
+		self.nesting = self.nesting.saturating_sub(1);
+		if self.nesting == 0 {
+			self.close();
+		}
+	}
+
+	/// Records the open group regardless of nesting.
+	fn close(&mut self) {
+		self.nesting = 0;
+		if let Some(group) = self.group.take() {
+			self.push_group(group);
+		}
+	}
+
+	/// Records a command which has already been applied.
+	pub fn push(&mut self, cmd: Command) {
+		match &mut self.group {
+			Some(group) => group.push(cmd),
+			None => self.push_group(vec![cmd]),
+		}
+	}
+
+	fn push_group(&mut self, group: Vec<Command>) {
+		if group.is_empty() {
+			return;
+		}
+		self.redo.clear();
+		self.undo.push_back(group);
+		self.truncate();
+	}
+
+	fn truncate(&mut self) {
+		while self.undo.len() > self.depth {
+			self.undo.pop_front();
+		}
+	}
+
+	/// Maximum number of groups that can be undone.
+	pub fn depth(&self) -> usize {
+		self.depth
+	}
+
+	/// Changes the maximum undo depth, dropping the oldest groups.
+	pub fn set_depth(&mut self, depth: usize) {
+		self.depth = depth;
+		self.truncate();
+	}
+
+	/// Pops the last group, the commands must be reverted in reverse order.
+	pub fn undo(&mut self) -> Option<&[Command]> {
+		self.close();
+		let group = self.undo.pop_back()?;
+		self.redo.push(group);
+		self.redo.last().map(|group| &group[..])
+	}
+
+	/// Pops the last undone group, the commands must be applied in order.
+	pub fn redo(&mut self) -> Option<&[Command]> {
+		self.close();
+		let group = self.redo.pop()?;
+		self.undo.push_back(group);
+		self.undo.back().map(|group| &group[..])
+	}
+
+	pub fn can_undo(&self) -> bool {
+		!self.undo.is_empty() || self.group.as_ref().is_some_and(|group| !group.is_empty())
+	}
+
+	pub fn can_redo(&self) -> bool {
+		!self.redo.is_empty()
+	}
+
+	pub fn clear(&mut self) {
+		self.undo.clear();
+		self.redo.clear();
+		self.group = None;
+		self.nesting = 0;
+	}
+}
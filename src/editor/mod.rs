@@ -0,0 +1,745 @@
+use std::collections::HashMap;
+use crate::visual::*;
+use crate::core;
+use cvmath::*;
+
+mod clipboard;
+mod document;
+mod history;
+mod palette;
+pub mod shapes;
+
+pub use self::clipboard::*;
+pub use self::document::*;
+pub use self::history::*;
+pub use self::palette::*;
+
+#[derive(Clone, Default)]
+pub struct EditorInput {
+	pub mouse: Vec2<i32>,
+	pub screen_size: Vec2<i32>,
+	/// Real time in seconds since the last frame.
+	pub dt: f32,
+	pub up: bool,
+	pub left: bool,
+	pub down: bool,
+	pub right: bool,
+	pub left_click: bool,
+	pub right_click: bool,
+	/// Game input while playtesting.
+	pub play: core::Input,
+}
+
+/// How the tool is applied with the mouse.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum DrawMode {
+	/// Applies the tool to every tile under the mouse stroke.
+	#[default]
+	Pencil,
+	/// Applies the tool along the line from where the mouse was pressed to where it is released.
+	Line,
+	/// Applies the tool to the rectangle from where the mouse was pressed to where it is released.
+	Rect,
+	/// Applies the tool to the contiguous terrain under the mouse.
+	Flood,
+	/// Selects the rectangle from where the mouse was pressed to where it is released.
+	Select,
+}
+
+/// Level property edited by typing.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TextField {
+	Name,
+	Hint,
+}
+
+/// Editor view presenting an [`EditorDocument`] and turning mouse input into edits.
+#[derive(Default)]
+pub struct EditorGame {
+	doc: EditorDocument,
+	/// Revision of the document the visual state shows, it is rebuilt from the document if none.
+	view_rev: Option<u64>,
+	game: VisualState,
+	/// Handles of the document's entities in the game, in document order.
+	entities: Vec<core::EntityHandle>,
+	input: EditorInput,
+	palette: Palette,
+	palette_drag: bool,
+	mode: DrawMode,
+	tile_pos: Option<Vec2<i32>>,
+	cursor_pos: Option<Vec2<i32>>,
+	drag_start: Option<Vec2<i32>>,
+	/// The mouse stroke in progress has an open history group.
+	stroke_open: bool,
+	selection: Option<(Vec2<i32>, Vec2<i32>)>,
+	conn_src: Vec2<i32>,
+	/// Index of the connection highlighted in the connection list.
+	selected_conn: Option<usize>,
+	/// The connection list is shown over the map.
+	show_conns: bool,
+	/// The level properties are shown over the map.
+	show_props: bool,
+	/// Property being typed into, it has an open history group.
+	text_field: Option<TextField>,
+	tileset: TileSet,
+	playtest: Option<Playtest>,
+}
+
+/// Editor camera saved while playtesting.
+struct Playtest {
+	target: Vec3<f32>,
+	target_fast: Vec3<f32>,
+}
+
+impl EditorGame {
+	pub fn init(&mut self, resources: Resources) {
+		self.game.resources = resources;
+		self.tileset = TileSet::default();
+		self.game.tiles = self.tileset.edit.clone();
+	}
+	pub fn load_data(&mut self, json: &str) -> Result<(), String> {
+		let defs = core::TerrainDefs::parse(json)?;
+		let tiles = TileSet::parse(json)?;
+		self.game.game.field.defs = defs;
+		self.game.tiles = if self.playtest.is_some() { tiles.play.clone() } else { tiles.edit.clone() };
+		self.tileset = tiles;
+		Ok(())
+	}
+	/// Reloads the changed resources and editor data, returns the errors of the resources that failed to reload.
+	pub fn hot_reload(&mut self, g: &mut shade::Graphics) -> Vec<String> {
+		let mut result = self.game.resources.hot_reload(g);
+		if let Some(json) = &result.data {
+			if let Err(err) = self.load_data(json) {
+				result.errors.push(err);
+			}
+		}
+		if result.tileset || result.data.is_some() {
+			self.game.terrain_cache.clear();
+		}
+		result.errors
+	}
+	pub fn load_level(&mut self, s: &str) -> Result<(), String> {
+		self.doc = EditorDocument::parse(s)?;
+		self.stroke_open = false;
+		self.text_field = None;
+		if self.playtest.take().is_some() {
+			self.game.tiles = self.tileset.edit.clone();
+		}
+		self.selection = None;
+		self.load_view();
+		Ok(())
+	}
+	pub fn document(&self) -> &EditorDocument {
+		&self.doc
+	}
+	pub fn document_mut(&mut self) -> &mut EditorDocument {
+		&mut self.doc
+	}
+	/// Visual state presenting the document, or the game while playtesting.
+	pub fn visual_state(&self) -> &VisualState {
+		&self.game
+	}
+	pub fn is_playtesting(&self) -> bool {
+		self.playtest.is_some()
+	}
+	/// Starts playing the level as currently edited or returns to editing.
+	///
+	/// The game is simulated on a copy of the level, when playtesting stops the view is rebuilt from the unchanged document.
+	pub fn toggle_playtest(&mut self) {
+		if let Some(playtest) = self.playtest.take() {
+			self.game.tiles = self.tileset.edit.clone();
+			self.game.camera.target = playtest.target;
+			self.game.camera.target_fast = playtest.target_fast;
+			self.view_rev = None;
+			self.update_view();
+		}
+		else {
+			// Finish any stroke in progress before the view is taken over by the game
+			self.end_stroke();
+			self.end_text();
+			self.drag_start = None;
+			self.tile_pos = None;
+			self.playtest = Some(Playtest {
+				target: self.game.camera.target,
+				target_fast: self.game.camera.target_fast,
+			});
+			self.game.tiles = self.tileset.play.clone();
+			self.game.load_dto(self.doc.to_dto());
+			self.game.camera.eye_offset = Vec3::new(0.0, 2.0 * 32.0, 400.0);
+			self.view_rev = None;
+		}
+	}
+	/// Closes the history group of the mouse stroke in progress.
+	fn end_stroke(&mut self) {
+		if self.stroke_open {
+			self.stroke_open = false;
+			self.doc.end();
+		}
+	}
+	/// Rebuilds the visual state from the document.
+	fn load_view(&mut self) {
+		self.doc.take_changes();
+		self.game.load_dto(self.doc.to_dto());
+		// Entities are created in document order
+		self.entities = self.game.game.events.iter().filter_map(|ev| match ev {
+			&core::GameEvent::EntityCreated { entity } => Some(entity),
+			_ => None,
+		}).collect();
+		self.view_rev = Some(self.doc.revision());
+	}
+	/// Applies the edits made to the document since the last update to the visual state.
+	fn update_view(&mut self) {
+		if self.view_rev == Some(self.doc.revision()) {
+			return;
+		}
+		// Keep the camera where it is while the objects are recreated
+		let (target, target_fast) = (self.game.camera.target, self.game.camera.target_fast);
+		let changes = self.doc.take_changes();
+		// Replacing the map may change its size, the view is rebuilt
+		if self.view_rev.is_none() || changes.iter().any(|cmd| matches!(cmd, Command::SetMap { .. })) {
+			self.load_view();
+		}
+		else {
+			for cmd in &changes {
+				self.apply_view(cmd);
+			}
+			self.view_rev = Some(self.doc.revision());
+		}
+		self.game.camera.target = target;
+		self.game.camera.target_fast = target_fast;
+	}
+	/// Applies the command to the game and syncs the objects with it.
+	fn apply_view(&mut self, cmd: &Command) {
+		self.game.game.events.clear();
+		match cmd {
+			&Command::SetTerrain { pos, new, .. } => {
+				self.game.game.field.set_terrain(pos, new);
+				self.game.terrain_cache.invalidate(pos);
+				self.game.remove_terrain_objects(pos);
+				self.game.create_terrain_object(pos);
+			}
+			&Command::PlaceEntity { index, args } => {
+				let handle = core::create(&mut self.game.game, &args);
+				self.entities.insert(usize::min(index, self.entities.len()), handle);
+			}
+			&Command::RemoveEntity { index, .. } => {
+				if index < self.entities.len() {
+					let handle = self.entities.remove(index);
+					self.game.game.ents.remove(handle);
+					// Unlike entities removed while playing the object disappears at once
+					if let Some(obj_handle) = self.game.objects.lookup.remove(&handle) {
+						self.game.objects.remove(obj_handle);
+					}
+					// The last player placed is the one played
+					if self.game.game.ps.entity == handle {
+						let ents = &self.game.game.ents;
+						let player = self.entities.iter().rev().cloned().find(|&handle| ents.get(handle).is_some_and(|ent| matches!(ent.kind, core::EntityKind::Player)));
+						self.game.game.ps.entity = player.unwrap_or_default();
+					}
+				}
+			}
+			&Command::AddConnection { index, conn } => {
+				let conns = &mut self.game.game.field.conns;
+				conns.insert(usize::min(index, conns.len()), conn);
+			}
+			&Command::RemoveConnection { index, .. } => {
+				let conns = &mut self.game.game.field.conns;
+				if index < conns.len() {
+					conns.remove(index);
+				}
+			}
+			Command::SetProperties { new, .. } => {
+				let field = &mut self.game.game.field;
+				field.name.clone_from(&new.name);
+				field.hint.clone_from(&new.hint);
+				field.password.clone_from(&new.password);
+				field.time = new.time;
+				field.chips = new.chips;
+				self.game.game.rand.rng = urandom::rng::Xoshiro256::from_seed(new.seed);
+			}
+			// Replacing the map rebuilds the view instead
+			Command::SetMap { .. } => {}
+		}
+		// Blocks and clone machines hide the entities under them
+		core::update_hidden_entities(&mut self.game.game);
+		let events = std::mem::take(&mut self.game.game.events);
+		self.game.sync(&events);
+	}
+	/// Grows (positive) or shrinks (negative) the map at each edge.
+	pub fn resize(&mut self, left: i32, top: i32, right: i32, bottom: i32) -> bool {
+		self.selection = None;
+		self.doc.resize(left, top, right, bottom)
+	}
+	pub fn camera_mut(&mut self) -> &mut Camera {
+		&mut self.game.camera
+	}
+	pub fn palette(&self) -> &Palette {
+		&self.palette
+	}
+	pub fn palette_mut(&mut self) -> &mut Palette {
+		&mut self.palette
+	}
+	pub fn set_mode(&mut self, mode: DrawMode) {
+		self.mode = mode;
+		if mode != DrawMode::Select {
+			self.selection = None;
+		}
+	}
+	/// Returns the inclusive bounds of the selected tiles.
+	pub fn selection(&self) -> Option<(Vec2<i32>, Vec2<i32>)> {
+		self.selection
+	}
+	/// Copies the selection to the clipboard.
+	pub fn copy(&mut self) -> bool {
+		let Some((min, max)) = self.selection else { return false };
+		self.doc.copy(min, max);
+		true
+	}
+	/// Copies the selection to the clipboard and clears it to floor.
+	pub fn cut(&mut self) -> bool {
+		let Some((min, max)) = self.selection else { return false };
+		self.doc.cut(min, max);
+		true
+	}
+	/// Pastes the clipboard with its top left corner at the tile under the mouse.
+	pub fn paste(&mut self) -> bool {
+		let Some(origin) = self.cursor_pos else { return false };
+		self.doc.paste(origin)
+	}
+	/// Returns the index of the highlighted connection.
+	pub fn selected_conn(&self) -> Option<usize> {
+		self.selected_conn.filter(|&index| index < self.doc.map().conns.len())
+	}
+	/// Highlights the next connection in the connection list.
+	pub fn next_conn(&mut self) {
+		let len = self.doc.map().conns.len();
+		self.selected_conn = if len == 0 { None } else { Some(self.selected_conn().map_or(0, |index| (index + 1) % len)) };
+	}
+	/// Highlights the previous connection in the connection list.
+	pub fn prev_conn(&mut self) {
+		let len = self.doc.map().conns.len();
+		self.selected_conn = if len == 0 { None } else { Some(self.selected_conn().map_or(len - 1, |index| (index + len - 1) % len)) };
+	}
+	/// Deletes the highlighted connection.
+	pub fn delete_conn(&mut self) -> bool {
+		let Some(index) = self.selected_conn() else { return false };
+		self.doc.remove_connection(index)
+	}
+	/// Shows or hides the connection list.
+	pub fn toggle_conn_list(&mut self) {
+		self.show_conns = !self.show_conns;
+	}
+	pub fn is_conn_list_shown(&self) -> bool {
+		self.show_conns
+	}
+	/// Shows or hides the level properties.
+	pub fn toggle_props(&mut self) {
+		self.show_props = !self.show_props;
+	}
+	pub fn is_props_shown(&self) -> bool {
+		self.show_props
+	}
+	/// Starts typing into the level property, everything typed until [`end_text`](Self::end_text) is undone as a whole.
+	pub fn begin_text(&mut self, field: TextField) {
+		self.end_text();
+		self.end_stroke();
+		self.doc.begin();
+		self.text_field = Some(field);
+	}
+	/// Stops typing into the level property.
+	pub fn end_text(&mut self) {
+		if self.text_field.take().is_some() {
+			self.doc.end();
+		}
+	}
+	/// Returns the property being typed into.
+	pub fn text_field(&self) -> Option<TextField> {
+		self.text_field
+	}
+	/// Types the character into the property, backspace removes the last character and other control characters are ignored.
+	pub fn type_char(&mut self, chr: char) -> bool {
+		let Some(field) = self.text_field else { return false };
+		self.doc.edit_properties(|props| {
+			let text = match field {
+				TextField::Name => &mut props.name,
+				TextField::Hint => &mut props.hint,
+			};
+			if chr == '\u{8}' {
+				text.pop();
+			}
+			else if !chr.is_control() {
+				text.push(chr);
+			}
+		});
+		true
+	}
+	/// Describes the level properties as shown in the properties panel, the property being typed into ends with a cursor.
+	pub fn props_list(&self) -> Vec<String> {
+		let props = self.doc.properties();
+		let cursor = |field| if self.text_field == Some(field) { "_" } else { "" };
+		vec![
+			format!("Name: {}{}", props.name, cursor(TextField::Name)),
+			format!("Hint: {}{}", props.hint, cursor(TextField::Hint)),
+			format!("Password: {}", props.password),
+			format!("Time: {}", if props.time > 0 { props.time.to_string() } else { String::from("---") }),
+			format!("Chips: {} of {}", props.chips, self.doc.count_chips()),
+		]
+	}
+	/// Describes the connections as shown in the connection list, the highlighted one is marked.
+	pub fn conn_list(&self) -> Vec<String> {
+		let map = self.doc.map();
+		map.conns.iter().enumerate().map(|(index, conn)| {
+			let selected = if self.selected_conn() == Some(index) { '>' } else { ' ' };
+			let kind = match map.conn_kind(conn) {
+				Ok(kind) => format!("{:?}", kind),
+				Err(err) => format!("Invalid: {}", err),
+			};
+			format!("{} {}: {},{} -> {},{} {}", selected, index, conn.src.x, conn.src.y, conn.dest.x, conn.dest.y, kind)
+		}).collect()
+	}
+	pub fn render(&mut self, g: &mut impl DrawBackend, input: &EditorInput) {
+		self.game.resources.screen_size = input.screen_size;
+
+		// The camera follows the player while playtesting
+		if self.playtest.is_some() {
+			self.game.advance(&input.play, input.dt);
+			self.game.hud.hidden = false;
+			self.game.draw(g);
+			self.input = input.clone();
+			return;
+		}
+
+		if input.left {
+			self.game.camera.target.x -= 5.0;
+		}
+		if input.right {
+			self.game.camera.target.x += 5.0;
+		}
+		if input.up {
+			self.game.camera.target.y -= 5.0;
+		}
+		if input.down {
+			self.game.camera.target.y += 5.0;
+		}
+
+		self.update_view();
+
+		self.game.camera.eye_offset = Vec3::<f32>(0.0, 8.0 * 32.0, 400.0) * 2.0;
+		self.game.camera.object_h = None;
+
+		self.game.dt = input.dt;
+		self.game.hud.hidden = true;
+		self.game.draw(g);
+
+		// Clicks on the palette panel select a tool instead of editing the map
+		let panel_size = self.palette.panel_size(input.screen_size);
+		let over_palette = input.mouse.x < panel_size.x && input.mouse.y < panel_size.y;
+		if !self.input.left_click && input.left_click {
+			self.palette_drag = over_palette;
+			if let Some(index) = self.palette.hit(input.mouse, input.screen_size) {
+				self.palette.select(index);
+			}
+		}
+		if !input.left_click {
+			self.palette_drag = false;
+		}
+
+		let mut mouse_pos = None;
+		let mut tile_pos = None;
+		if !over_palette && !self.palette_drag {
+			if let Some(p) = self.game.camera.screen_to_world(input.mouse) {
+				let pi = p.xy().map(|c| f32::floor(c / 32.0) as i32);
+				mouse_pos = Some(p);
+				tile_pos = Some(pi);
+			}
+		}
+
+		if tile_pos.is_some() {
+			self.cursor_pos = tile_pos;
+		}
+
+		// Every mouse stroke is undone as a whole
+		if !self.input.left_click && input.left_click {
+			self.end_text();
+			self.end_stroke();
+			self.doc.begin();
+			self.stroke_open = true;
+			self.drag_start = tile_pos;
+			if let (DrawMode::Flood, Some(tile_pos)) = (self.mode, tile_pos) {
+				for pos in shapes::flood(self.doc.map(), tile_pos) {
+					self.doc.apply_tool(self.palette.tool(), pos);
+				}
+			}
+		}
+		if input.left_click {
+			if self.mode == DrawMode::Pencil && self.tile_pos != tile_pos {
+				if let Some(tile_pos) = tile_pos {
+					self.doc.apply_tool(self.palette.tool(), tile_pos);
+				}
+				self.tile_pos = tile_pos;
+			}
+		}
+		else {
+			if self.input.left_click {
+				if let (Some(start), Some(end)) = (self.drag_start, self.cursor_pos) {
+					match self.mode {
+						DrawMode::Line => for pos in shapes::line(start, end) { self.doc.apply_tool(self.palette.tool(), pos) },
+						DrawMode::Rect => for pos in shapes::rect(start, end) { self.doc.apply_tool(self.palette.tool(), pos) },
+						DrawMode::Select => self.selection = Some(shapes::rect_bounds(start, end)),
+						_ => (),
+					}
+				}
+				self.end_stroke();
+			}
+			self.drag_start = None;
+			self.tile_pos = None;
+		}
+
+		if !self.input.right_click && input.right_click {
+			if let Some(tile_pos) = tile_pos {
+				self.conn_src = tile_pos;
+			}
+		}
+		if self.input.right_click && !input.right_click {
+			if let Some(tile_pos) = tile_pos {
+				self.doc.connect(self.conn_src, tile_pos);
+			}
+		}
+
+		// Show the edits made this frame
+		self.update_view();
+
+		g.begin();
+
+		let world_pass = DrawPass {
+			shader: self.game.resources.shader,
+			uniform: self.game.resources.tileset_uniform(self.game.camera.view_proj_mat),
+			depth_test: true,
+			blend: Blend::Solid,
+			viewport: input.screen_size,
+		};
+
+		if let Some(p) = mouse_pos {
+			g.clear(None);
+			g.begin_pass(&world_pass);
+			{
+				let s = 2.0;
+				let z = 40.0;
+				g.add_prim(&render::QUAD_INDICES, &[
+					render::Vertex { pos: Vec3::new(p.x-s, p.y-s, p.z), uv: Vec2::new(0.0, 0.0), color: [255, 0, 0, 255] },
+					render::Vertex { pos: Vec3::new(p.x+s, p.y-s, p.z), uv: Vec2::new(1.0, 0.0), color: [255, 0, 0, 255] },
+					render::Vertex { pos: Vec3::new(p.x+s, p.y+s, p.z + z), uv: Vec2::new(1.0, 1.0), color: [255, 0, 0, 255] },
+					render::Vertex { pos: Vec3::new(p.x-s, p.y+s, p.z + z), uv: Vec2::new(0.0, 1.0), color: [255, 0, 0, 255] },
+				]);
+			}
+
+			match self.palette.tool() {
+				Tool::Terrain(index) => {
+					render::draw_tile(g, &self.game.resources.atlas, index, p, &self.game.tiles);
+				}
+				Tool::Entity(args) => {
+					let sprite = sprite_for_kind(args.kind, args.face_dir, &self.game.game.ps);
+					render::draw_sprite(g, &self.game.resources.atlas, sprite, p);
+				}
+				Tool::Erase => (),
+			}
+			g.end_pass();
+		}
+
+		{
+			g.begin_pass(&world_pass);
+
+			let map = self.doc.map();
+			for (index, conn) in map.conns.iter().enumerate() {
+				let color = match map.conn_kind(conn) {
+					Ok(kind) => conn_color(kind),
+					Err(_) => [255, 0, 255, 255],
+				};
+				{
+					let src = conn.src.map(|c| c as f32 * 32.0 + 16.0);
+					let dest = conn.dest.map(|c| c as f32 * 32.0 + 16.0);
+					let pth = (dest - src).normalize() * 12.0;
+					let pta = (dest - pth) + pth.ccw() * 0.5;
+					let ptb = (dest - pth) + pth.cw() * 0.5;
+					g.add_lines(&[0, 1, 2, 1, 3, 1], &[
+						render::Vertex { pos: src.vec3(0.0), uv: Vec2::ZERO, color },
+						render::Vertex { pos: dest.vec3(0.0), uv: Vec2::ZERO, color },
+						render::Vertex { pos: pta.vec3(0.0), uv: Vec2::ZERO, color },
+						render::Vertex { pos: ptb.vec3(0.0), uv: Vec2::ZERO, color },
+					]);
+				}
+
+				if self.selected_conn == Some(index) {
+					draw_outline(g, conn.src, conn.src, color);
+					draw_outline(g, conn.dest, conn.dest, color);
+				}
+			}
+
+			if let Some((min, max)) = self.selection {
+				draw_outline(g, min, max, [255, 255, 0, 255]);
+			}
+			if let (true, Some(start), Some(end)) = (input.left_click, self.drag_start, self.cursor_pos) {
+				match self.mode {
+					DrawMode::Line => for pos in shapes::line(start, end) { draw_outline(g, pos, pos, [255, 255, 255, 255]) },
+					DrawMode::Rect => { let (min, max) = shapes::rect_bounds(start, end); draw_outline(g, min, max, [255, 255, 255, 255]) },
+					DrawMode::Select => { let (min, max) = shapes::rect_bounds(start, end); draw_outline(g, min, max, [255, 255, 0, 255]) },
+					_ => (),
+				}
+			}
+			g.end_pass();
+		}
+
+		self.draw_palette(g, input.screen_size);
+		if self.show_conns {
+			self.draw_conn_list(g, input.screen_size);
+		}
+		if self.show_props || self.text_field.is_some() {
+			let lines = self.props_list().into_iter().map(|line| (line, PANEL_STYLE.color)).collect::<Vec<_>>();
+			self.draw_text_panel(g, input.screen_size, &lines, true);
+		}
+
+		g.end();
+
+		self.input = input.clone();
+	}
+}
+
+impl EditorGame {
+	fn draw_palette(&self, g: &mut impl DrawBackend, screen_size: Vec2<i32>) {
+		// Map pixels to clip space with the y axis pointing down
+		let mut transform = Mat4::IDENTITY;
+		transform.a11 = 2.0 / screen_size.x as f32;
+		transform.a14 = -1.0;
+		transform.a22 = -2.0 / screen_size.y as f32;
+		transform.a24 = 1.0;
+		transform.a33 = 0.0;
+		g.begin_pass(&DrawPass {
+			shader: self.game.resources.shader,
+			uniform: self.game.resources.tileset_uniform(transform),
+			depth_test: false,
+			blend: Blend::Alpha,
+			viewport: screen_size,
+		});
+
+		let atlas = &self.game.resources.atlas;
+		let pad = (PALETTE_CELL - 32) as f32 * 0.5;
+		for index in 0..self.palette.items.len() {
+			let cell = self.palette.cell_pos(index, screen_size).map(|c| c as f32);
+			let pos = Vec3(cell.x + pad, cell.y + pad, 0.0);
+			match self.palette.item(index) {
+				Tool::Terrain(terrain) => {
+					render::draw_sprite(g, atlas, self.game.tiles[terrain as usize].sprite, pos);
+					// Fire is drawn as an object on top of the floor
+					if terrain == core::Terrain::Fire {
+						render::draw_sprite(g, atlas, Sprite::Fire, pos);
+					}
+				}
+				Tool::Entity(args) => {
+					render::draw_sprite(g, atlas, sprite_for_kind(args.kind, args.face_dir, &core::PlayerState::default()), pos);
+				}
+				Tool::Erase => {
+					let (a, b) = (pos.xy(), pos.xy() + Vec2(32.0, 32.0));
+					g.add_lines(&[0, 1, 2, 3], &[
+						render::Vertex { pos: Vec3(a.x, a.y, 0.0), uv: Vec2::ZERO, color: [255, 0, 0, 255] },
+						render::Vertex { pos: Vec3(b.x, b.y, 0.0), uv: Vec2::ZERO, color: [255, 0, 0, 255] },
+						render::Vertex { pos: Vec3(b.x, a.y, 0.0), uv: Vec2::ZERO, color: [255, 0, 0, 255] },
+						render::Vertex { pos: Vec3(a.x, b.y, 0.0), uv: Vec2::ZERO, color: [255, 0, 0, 255] },
+					]);
+				}
+			}
+		}
+
+		let cell = self.palette.cell_pos(self.palette.selected, screen_size).map(|c| c as f32);
+		draw_rect(g, cell, cell + Vec2(PALETTE_CELL as f32, PALETTE_CELL as f32), 0.0, [255, 255, 0, 255]);
+
+		g.end_pass();
+	}
+
+	fn draw_conn_list(&self, g: &mut impl DrawBackend, screen_size: Vec2<i32>) {
+		let map = self.doc.map();
+		// Lines are colored like their arrows
+		let mut lines = self.conn_list().into_iter().zip(&map.conns).map(|(line, conn)| {
+			let color = match map.conn_kind(conn) {
+				Ok(kind) => conn_color(kind),
+				Err(_) => [255, 0, 255, 255],
+			};
+			(line, color)
+		}).collect::<Vec<_>>();
+		if lines.is_empty() {
+			lines.push((String::from("No connections"), PANEL_STYLE.color));
+		}
+		self.draw_text_panel(g, screen_size, &lines, false);
+	}
+
+	/// Draws the lines of text on a panel in the top or bottom right corner of the screen.
+	fn draw_text_panel(&self, g: &mut impl DrawBackend, screen_size: Vec2<i32>, lines: &[(String, [u8; 4])], bottom: bool) {
+		// Map pixels to clip space with the y axis pointing down
+		let mut transform = Mat4::IDENTITY;
+		transform.a11 = 2.0 / screen_size.x as f32;
+		transform.a14 = -1.0;
+		transform.a22 = -2.0 / screen_size.y as f32;
+		transform.a24 = 1.0;
+		transform.a33 = 0.0;
+
+		// The panel uses the tileset, the text is drawn on top with the font texture
+		let mut cv = render::Mesh::default();
+		let mut tv = render::Mesh::default();
+
+		let font = &self.game.resources.font_atlas;
+		let style = TextStyle { max_width: Some(screen_size.x as f32 * 0.5), ..PANEL_STYLE };
+		let layouts = lines.iter().map(|(line, _)| font.layout(line, &style)).collect::<Vec<_>>();
+		let text_size = layouts.iter().fold(Vec2(0.0f32, 0.0f32), |size, layout| Vec2(f32::max(size.x, layout.size.x), size.y + layout.size.y));
+		let panel_size = text_size + Vec2(PANEL_MARGIN * 2.0, PANEL_MARGIN * 2.0);
+		let min = Vec2(
+			screen_size.x as f32 - PANEL_MARGIN - panel_size.x,
+			if bottom { screen_size.y as f32 - PANEL_MARGIN - panel_size.y } else { PANEL_MARGIN },
+		);
+		draw_panel(&mut cv, &self.game.resources.atlas.frame(Sprite::Floor, 0.0), min, min + panel_size);
+
+		let mut pos = min + Vec2(PANEL_MARGIN, PANEL_MARGIN);
+		for ((line, color), layout) in lines.iter().zip(&layouts) {
+			draw_text(&mut tv, font, pos, line, &TextStyle { color: *color, ..style });
+			pos.y += layout.size.y;
+		}
+
+		let pass = DrawPass {
+			shader: self.game.resources.shader,
+			uniform: self.game.resources.tileset_uniform(transform),
+			depth_test: false,
+			blend: Blend::Alpha,
+			viewport: screen_size,
+		};
+		g.begin_pass(&pass);
+		cv.draw(g);
+		g.end_pass();
+		g.begin_pass(&DrawPass { uniform: self.game.resources.font_uniform(transform), ..pass });
+		tv.draw(g);
+		g.end_pass();
+	}
+}
+
+const PANEL_STYLE: TextStyle = TextStyle { scale: 2.0, color: [0, 0, 0, 255], align: TextAlign::Left, max_width: None };
+const PANEL_MARGIN: f32 = 8.0;
+
+/// Color of the connection arrows by kind, invalid connections are drawn magenta.
+fn conn_color(kind: core::ConnKind) -> [u8; 4] {
+	match kind {
+		core::ConnKind::Clone => [255, 0, 0, 255],
+		core::ConnKind::Trap => [160, 96, 32, 255],
+		core::ConnKind::Teleport => [0, 128, 255, 255],
+	}
+}
+
+/// Draws the outline around the tiles between the inclusive bounds.
+fn draw_outline(cv: &mut impl DrawBackend, min: Vec2<i32>, max: Vec2<i32>, color: [u8; 4]) {
+	draw_rect(cv, min.map(|c| c as f32 * 32.0), max.map(|c| c as f32 * 32.0 + 32.0), 1.0, color);
+}
+
+fn draw_rect(cv: &mut impl DrawBackend, min: Vec2<f32>, max: Vec2<f32>, z: f32, color: [u8; 4]) {
+	cv.add_lines(&[0, 1, 1, 2, 2, 3, 3, 0], &[
+		render::Vertex { pos: Vec3(min.x, min.y, z), uv: Vec2::ZERO, color },
+		render::Vertex { pos: Vec3(max.x, min.y, z), uv: Vec2::ZERO, color },
+		render::Vertex { pos: Vec3(max.x, max.y, z), uv: Vec2::ZERO, color },
+		render::Vertex { pos: Vec3(min.x, max.y, z), uv: Vec2::ZERO, color },
+	]);
+}
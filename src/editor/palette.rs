@@ -0,0 +1,109 @@
+use super::*;
+
+#[derive(Copy, Clone, Debug)]
+pub enum Tool {
+	Terrain(core::Terrain),
+	Entity(core::EntityArgs),
+	Erase,
+}
+impl Default for Tool {
+	fn default() -> Self {
+		Tool::Terrain(core::Terrain::Floor)
+	}
+}
+
+/// Returns if the entity kind is placed with a facing direction.
+pub fn has_facing(kind: core::EntityKind) -> bool {
+	matches!(kind,
+		core::EntityKind::Bug | core::EntityKind::FireBall | core::EntityKind::PinkBall | core::EntityKind::Tank |
+		core::EntityKind::Glider | core::EntityKind::Teeth | core::EntityKind::Walker | core::EntityKind::Blob |
+		core::EntityKind::Paramecium)
+}
+
+/// Size of a palette cell in pixels.
+pub const PALETTE_CELL: i32 = 36;
+
+/// Every tool the editor can paint with.
+pub struct Palette {
+	pub items: Vec<Tool>,
+	pub selected: usize,
+	/// Facing of the placed entities.
+	pub face_dir: core::Dir,
+}
+
+impl Default for Palette {
+	fn default() -> Self {
+		let mut items = Vec::with_capacity(1 + core::TERRAIN_COUNT + core::ENTITY_KIND_COUNT);
+		items.push(Tool::Erase);
+		items.extend(core::ALL_TERRAIN.iter().map(|&terrain| Tool::Terrain(terrain)));
+		items.extend(core::ALL_ENTITY_KINDS.iter().map(|&kind| Tool::Entity(core::EntityArgs { kind, pos: Vec2::ZERO, face_dir: None })));
+		let selected = items.iter().position(|tool| matches!(tool, Tool::Terrain(core::Terrain::Floor))).unwrap_or(0);
+		Palette { items, selected, face_dir: core::Dir::Up }
+	}
+}
+
+impl Palette {
+	/// Returns the selected tool with the current facing applied.
+	pub fn tool(&self) -> Tool {
+		self.item(self.selected)
+	}
+	/// Returns the tool at the index with the current facing applied.
+	pub fn item(&self, index: usize) -> Tool {
+		match self.items[index] {
+			Tool::Entity(args) => Tool::Entity(core::EntityArgs {
+				face_dir: if has_facing(args.kind) { Some(self.face_dir) } else { None },
+				..args
+			}),
+			tool => tool,
+		}
+	}
+	pub fn select(&mut self, index: usize) {
+		if index < self.items.len() {
+			self.selected = index;
+		}
+	}
+	pub fn select_erase(&mut self) {
+		if let Some(index) = self.items.iter().position(|tool| matches!(tool, Tool::Erase)) {
+			self.selected = index;
+		}
+	}
+	pub fn next(&mut self) {
+		self.selected = (self.selected + 1) % self.items.len();
+	}
+	pub fn prev(&mut self) {
+		self.selected = (self.selected + self.items.len() - 1) % self.items.len();
+	}
+	/// Turns the facing of placed entities clockwise.
+	pub fn cycle_facing(&mut self) {
+		self.face_dir = self.face_dir.turn_right();
+	}
+
+	/// Number of rows of the palette panel on the screen.
+	fn rows(&self, screen_size: Vec2<i32>) -> i32 {
+		i32::max(1, screen_size.y / PALETTE_CELL)
+	}
+	/// Size of the palette panel in pixels, the panel is at the left edge of the screen.
+	pub fn panel_size(&self, screen_size: Vec2<i32>) -> Vec2<i32> {
+		let rows = self.rows(screen_size);
+		let columns = (self.items.len() as i32 + rows - 1) / rows;
+		Vec2(columns * PALETTE_CELL, i32::min(rows, self.items.len() as i32) * PALETTE_CELL)
+	}
+	/// Returns the top left corner of the item's cell in pixels.
+	pub fn cell_pos(&self, index: usize, screen_size: Vec2<i32>) -> Vec2<i32> {
+		let rows = self.rows(screen_size);
+		Vec2(index as i32 / rows, index as i32 % rows) * PALETTE_CELL
+	}
+	/// Returns the index of the item under the mouse.
+	pub fn hit(&self, mouse: Vec2<i32>, screen_size: Vec2<i32>) -> Option<usize> {
+		if mouse.x < 0 || mouse.y < 0 {
+			return None;
+		}
+		let rows = self.rows(screen_size);
+		let cell = Vec2(mouse.x / PALETTE_CELL, mouse.y / PALETTE_CELL);
+		if cell.y >= rows {
+			return None;
+		}
+		let index = (cell.x * rows + cell.y) as usize;
+		if index < self.items.len() { Some(index) } else { None }
+	}
+}
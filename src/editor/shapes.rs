@@ -0,0 +1,72 @@
+use super::*;
+
+/// Returns the inclusive bounds of the rectangle spanned by two corners.
+pub fn rect_bounds(a: Vec2<i32>, b: Vec2<i32>) -> (Vec2<i32>, Vec2<i32>) {
+	(Vec2(i32::min(a.x, b.x), i32::min(a.y, b.y)), Vec2(i32::max(a.x, b.x), i32::max(a.y, b.y)))
+}
+
+/// Tiles of the filled rectangle spanned by two corners.
+pub fn rect(a: Vec2<i32>, b: Vec2<i32>) -> Vec<Vec2<i32>> {
+	let (min, max) = rect_bounds(a, b);
+	let mut tiles = Vec::new();
+	for y in min.y..=max.y {
+		for x in min.x..=max.x {
+			tiles.push(Vec2(x, y));
+		}
+	}
+	tiles
+}
+
+/// Tiles of the line between two tiles.
+pub fn line(a: Vec2<i32>, b: Vec2<i32>) -> Vec<Vec2<i32>> {
+	let dx = i32::abs(b.x - a.x);
+	let dy = -i32::abs(b.y - a.y);
+	let sx = if a.x < b.x { 1 } else { -1 };
+	let sy = if a.y < b.y { 1 } else { -1 };
+	let mut err = dx + dy;
+	let mut pos = a;
+	let mut tiles = Vec::new();
+	loop {
+		tiles.push(pos);
+		if pos == b {
+			break;
+		}
+		let e2 = err * 2;
+		if e2 >= dy {
+			err += dy;
+			pos.x += sx;
+		}
+		if e2 <= dx {
+			err += dx;
+			pos.y += sy;
+		}
+	}
+	tiles
+}
+
+/// Tiles of the contiguous region of the same terrain as the starting tile.
+pub fn flood(map: &MapState, start: Vec2<i32>) -> Vec<Vec2<i32>> {
+	if !map.in_bounds(start) {
+		return Vec::new();
+	}
+	let terrain = map.get_terrain(start);
+	let mut visited = vec![false; map.width as usize * map.height as usize];
+	let mut stack = vec![start];
+	let mut tiles = Vec::new();
+	while let Some(pos) = stack.pop() {
+		if !map.in_bounds(pos) {
+			continue;
+		}
+		let index = (pos.y * map.width + pos.x) as usize;
+		if visited[index] || map.get_terrain(pos) != terrain {
+			continue;
+		}
+		visited[index] = true;
+		tiles.push(pos);
+		stack.push(Vec2(pos.x + 1, pos.y));
+		stack.push(Vec2(pos.x - 1, pos.y));
+		stack.push(Vec2(pos.x, pos.y + 1));
+		stack.push(Vec2(pos.x, pos.y - 1));
+	}
+	tiles
+}
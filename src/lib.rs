@@ -3,3 +3,5 @@
 pub mod core;
 pub mod visual;
 pub mod editor;
+pub mod raster;
+pub mod bindings;
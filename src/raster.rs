@@ -0,0 +1,198 @@
+use std::{fs, io};
+use crate::core;
+use crate::visual::{sprite_for_ent, Model, Sprite, SpriteAtlas, TileGfx};
+use cvmath::*;
+
+/// RGBA image with 8 bits per channel.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Image {
+	pub width: i32,
+	pub height: i32,
+	pub pixels: Vec<[u8; 4]>,
+}
+
+impl Image {
+	pub fn new(width: i32, height: i32, color: [u8; 4]) -> Image {
+		Image { width, height, pixels: vec![color; width as usize * height as usize] }
+	}
+
+	pub fn load_png(path: &str) -> Result<Image, String> {
+		let file = fs::File::open(path).map_err(|err| format!("{}: {}", path, err))?;
+		Image::decode_png(io::BufReader::new(file))
+	}
+
+	pub fn decode_png<R: io::Read>(reader: R) -> Result<Image, String> {
+		let mut decoder = png::Decoder::new(reader);
+		decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+		let mut reader = decoder.read_info().map_err(|err| err.to_string())?;
+		let mut buf = vec![0; reader.output_buffer_size()];
+		let info = reader.next_frame(&mut buf).map_err(|err| err.to_string())?;
+
+		let pixels = buf[..info.buffer_size()].chunks_exact(info.color_type.samples()).map(|px| match *px {
+			[l] => [l, l, l, 255],
+			[l, a] => [l, l, l, a],
+			[r, g, b] => [r, g, b, 255],
+			[r, g, b, a] => [r, g, b, a],
+			_ => unreachable!(),
+		}).collect();
+
+		Ok(Image { width: info.width as i32, height: info.height as i32, pixels })
+	}
+
+	pub fn save_png(&self, path: &str) -> Result<(), String> {
+		let file = fs::File::create(path).map_err(|err| format!("{}: {}", path, err))?;
+		self.encode_png(io::BufWriter::new(file))
+	}
+
+	pub fn encode_png<W: io::Write>(&self, writer: W) -> Result<(), String> {
+		let mut encoder = png::Encoder::new(writer, self.width as u32, self.height as u32);
+		encoder.set_color(png::ColorType::Rgba);
+		encoder.set_depth(png::BitDepth::Eight);
+		let mut writer = encoder.write_header().map_err(|err| err.to_string())?;
+		let data = self.pixels.iter().flatten().cloned().collect::<Vec<u8>>();
+		writer.write_image_data(&data).map_err(|err| err.to_string())
+	}
+
+	#[inline]
+	pub fn get(&self, x: i32, y: i32) -> [u8; 4] {
+		if x < 0 || y < 0 || x >= self.width || y >= self.height {
+			return [0, 0, 0, 0];
+		}
+		self.pixels[(y * self.width + x) as usize]
+	}
+
+	/// Alpha blends the color over the pixel.
+	#[inline]
+	pub fn blend(&mut self, x: i32, y: i32, color: [u8; 4]) {
+		if x < 0 || y < 0 || x >= self.width || y >= self.height {
+			return;
+		}
+		let dest = &mut self.pixels[(y * self.width + x) as usize];
+		let a = color[3] as u32;
+		for i in 0..3 {
+			dest[i] = ((color[i] as u32 * a + dest[i] as u32 * (255 - a)) / 255) as u8;
+		}
+		dest[3] = u32::max(dest[3] as u32, a) as u8;
+	}
+}
+
+/// Options for [`render_level`].
+#[derive(Clone, Debug)]
+pub struct RenderOptions {
+	/// Output pixels per tileset texel.
+	pub scale: f32,
+	/// Draw lines between tiles.
+	pub grid: bool,
+	/// Overlay connections as arrows.
+	pub connections: bool,
+	pub background: [u8; 4],
+}
+
+impl Default for RenderOptions {
+	fn default() -> Self {
+		RenderOptions {
+			scale: 1.0,
+			grid: false,
+			connections: false,
+			background: [51, 51, 128, 255],
+		}
+	}
+}
+
+/// Renders the terrain and entities of the game state.
+///
+/// The tileset image is the source texture as stored on disk, before any gutter is added.
+pub fn render_level(game: &core::GameState, tileset: &Image, atlas: &SpriteAtlas, tiles: &[TileGfx], opts: &RenderOptions) -> Image {
+	let field = &game.field;
+	let tile_px = i32::max(1, (atlas.tile_size as f32 * opts.scale).round() as i32);
+	let mut image = Image::new(field.width * tile_px, field.height * tile_px, opts.background);
+
+	// Render the terrain
+	for y in 0..field.height {
+		for x in 0..field.width {
+			let terrain = field.get_terrain(Vec2(x, y));
+			let tile = tiles[terrain as usize];
+			let pos = Vec2(x, y) * tile_px;
+			if tile.sprite != Sprite::Blank && tile.model != Model::Empty {
+				blit_sprite(&mut image, tileset, atlas, tile.sprite, pos, tile_px);
+			}
+			// Terrain drawn as objects by the visual state
+			match terrain {
+				core::Terrain::Fire => blit_sprite(&mut image, tileset, atlas, Sprite::Fire, pos, tile_px),
+				core::Terrain::ToggleWall => blit_sprite(&mut image, tileset, atlas, Sprite::Wall, pos, tile_px),
+				_ => (),
+			}
+		}
+	}
+
+	// Render the entities, blocks are drawn on top of what they hide
+	let mut ents = game.ents.map.values().collect::<Vec<_>>();
+	ents.sort_by_key(|ent| (matches!(ent.kind, core::EntityKind::Block), ent.handle.0));
+	for ent in ents {
+		let sprite = sprite_for_ent(ent, &game.ps);
+		blit_sprite(&mut image, tileset, atlas, sprite, ent.pos * tile_px, tile_px);
+	}
+
+	if opts.grid {
+		for y in 0..image.height {
+			for x in 0..image.width {
+				if x % tile_px == 0 || y % tile_px == 0 {
+					image.blend(x, y, [0, 0, 0, 64]);
+				}
+			}
+		}
+	}
+
+	if opts.connections {
+		for conn in &field.conns {
+			draw_arrow(&mut image, conn, tile_px, [0, 0, 255, 255]);
+		}
+	}
+
+	image
+}
+
+fn blit_sprite(image: &mut Image, tileset: &Image, atlas: &SpriteAtlas, sprite: Sprite, pos: Vec2<i32>, tile_px: i32) {
+	let def = atlas.get(sprite);
+	let src = def.index * atlas.tile_size;
+	for y in 0..tile_px {
+		for x in 0..tile_px {
+			let u = src.x + x * atlas.tile_size / tile_px;
+			let v = src.y + y * atlas.tile_size / tile_px;
+			let color = tileset.get(u, v);
+			// Same cutoff as the fragment shader
+			if color[3] < 51 {
+				continue;
+			}
+			image.blend(pos.x + x, pos.y + y, color);
+		}
+	}
+}
+
+fn draw_arrow(image: &mut Image, conn: &core::Connection, tile_px: i32, color: [u8; 4]) {
+	let half = tile_px as f32 * 0.5;
+	let (sx, sy) = (conn.src.x as f32 * tile_px as f32 + half, conn.src.y as f32 * tile_px as f32 + half);
+	let (dx, dy) = (conn.dest.x as f32 * tile_px as f32 + half, conn.dest.y as f32 * tile_px as f32 + half);
+
+	let len = f32::sqrt((dx - sx) * (dx - sx) + (dy - sy) * (dy - sy));
+	if len == 0.0 {
+		return;
+	}
+	// Arrow head size relative to the tile like the editor overlay
+	let head = tile_px as f32 * (12.0 / 32.0);
+	let (hx, hy) = ((dx - sx) / len * head, (dy - sy) / len * head);
+
+	draw_line(image, (sx, sy), (dx, dy), color);
+	draw_line(image, (dx - hx + hy * 0.5, dy - hy - hx * 0.5), (dx, dy), color);
+	draw_line(image, (dx - hx - hy * 0.5, dy - hy + hx * 0.5), (dx, dy), color);
+}
+
+fn draw_line(image: &mut Image, (x0, y0): (f32, f32), (x1, y1): (f32, f32), color: [u8; 4]) {
+	let steps = f32::max(f32::abs(x1 - x0), f32::abs(y1 - y0)).ceil() as i32;
+	for i in 0..=steps {
+		let t = if steps > 0 { i as f32 / steps as f32 } else { 0.0 };
+		let x = x0 + (x1 - x0) * t;
+		let y = y0 + (y1 - y0) * t;
+		image.blend(x as i32, y as i32, color);
+	}
+}
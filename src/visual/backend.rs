@@ -0,0 +1,247 @@
+use super::*;
+
+/// Blending of the primitives with the frame.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Blend {
+	#[default]
+	Solid,
+	Alpha,
+}
+
+/// Render state of the primitives in a pass.
+#[derive(Copy, Clone)]
+pub struct DrawPass {
+	pub shader: shade::Shader,
+	pub uniform: render::Uniform,
+	/// Primitives are tested against the depth buffer.
+	pub depth_test: bool,
+	pub blend: Blend,
+	/// Size of the viewport in pixels.
+	pub viewport: Vec2<i32>,
+}
+
+/// Destination of everything the visuals draw, eg. the graphics context or a recording for tests.
+pub trait DrawBackend: PrimSink {
+	/// Starts a frame.
+	fn begin(&mut self);
+	/// Clears the depth buffer and the color buffer if a color is given.
+	fn clear(&mut self, color: Option<Vec4<f32>>);
+	/// Primitives added until the pass ends are drawn with its render state.
+	fn begin_pass(&mut self, pass: &DrawPass);
+	/// Changes the blending of the primitives added after it in the current pass.
+	fn set_blend(&mut self, blend: Blend);
+	/// Adds line segments with pairs of indices relative to the given vertices.
+	fn add_lines(&mut self, indices: &[u32], vertices: &[Vertex]);
+	fn end_pass(&mut self);
+	fn end(&mut self);
+}
+
+//----------------------------------------------------------------
+// Shade backend
+
+/// Draws with the graphics context, every pass is a canvas drawn to the back buffer when the pass ends.
+pub struct ShadeBackend<'a> {
+	pub g: &'a mut shade::Graphics,
+	cv: shade::d2::Canvas<Vertex, Uniform>,
+}
+
+impl<'a> ShadeBackend<'a> {
+	pub fn new(g: &'a mut shade::Graphics) -> ShadeBackend<'a> {
+		ShadeBackend { g, cv: shade::d2::Canvas::new() }
+	}
+}
+
+fn blend_mode(blend: Blend) -> shade::BlendMode {
+	match blend {
+		Blend::Solid => shade::BlendMode::Solid,
+		Blend::Alpha => shade::BlendMode::Alpha,
+	}
+}
+
+impl<'a> PrimSink for ShadeBackend<'a> {
+	fn add_prim(&mut self, indices: &[u32], vertices: &[Vertex]) {
+		self.cv.add_prim(indices, vertices);
+	}
+}
+
+impl<'a> DrawBackend for ShadeBackend<'a> {
+	fn begin(&mut self) {
+		self.g.begin().unwrap();
+	}
+	fn clear(&mut self, color: Option<Vec4<f32>>) {
+		self.g.clear(&shade::ClearArgs {
+			surface: shade::Surface::BACK_BUFFER,
+			color,
+			depth: Some(1.0),
+			..Default::default()
+		}).unwrap();
+	}
+	fn begin_pass(&mut self, pass: &DrawPass) {
+		self.cv = shade::d2::Canvas::new();
+		self.cv.shader = pass.shader;
+		self.cv.depth_test = if pass.depth_test { Some(shade::DepthTest::Less) } else { None };
+		self.cv.blend_mode = blend_mode(pass.blend);
+		self.cv.viewport = cvmath::Rect::vec(pass.viewport);
+		self.cv.push_uniform(pass.uniform);
+	}
+	fn set_blend(&mut self, blend: Blend) {
+		self.cv.blend_mode = blend_mode(blend);
+	}
+	fn add_lines(&mut self, indices: &[u32], vertices: &[Vertex]) {
+		let mut p = self.cv.begin(shade::PrimType::Lines, vertices.len(), indices.len() / 2);
+		for pair in indices.chunks_exact(2) {
+			p.add_index2(pair[0], pair[1]);
+		}
+		p.add_vertices(vertices);
+	}
+	fn end_pass(&mut self) {
+		self.cv.draw(self.g, shade::Surface::BACK_BUFFER).unwrap();
+	}
+	fn end(&mut self) {
+		self.g.end().unwrap();
+	}
+}
+
+//----------------------------------------------------------------
+// Recording backend
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PrimKind {
+	Triangles,
+	Lines,
+}
+
+/// Consecutive primitives of the same kind and blending.
+#[derive(Clone, Debug)]
+pub struct RecordedBatch {
+	pub kind: PrimKind,
+	pub blend: Blend,
+	pub indices: Vec<u32>,
+	pub vertices: Vec<Vertex>,
+}
+
+impl RecordedBatch {
+	#[inline]
+	pub fn prim_count(&self) -> usize {
+		match self.kind {
+			PrimKind::Triangles => self.indices.len() / 3,
+			PrimKind::Lines => self.indices.len() / 2,
+		}
+	}
+}
+
+#[derive(Clone)]
+pub struct RecordedPass {
+	pub pass: DrawPass,
+	pub batches: Vec<RecordedBatch>,
+}
+
+/// Captures the primitives instead of drawing them, for testing what would be rendered without a graphics context.
+#[derive(Clone, Default)]
+pub struct Recorder {
+	/// Number of frames begun.
+	pub frames: usize,
+	/// Clear color of every clear.
+	pub clears: Vec<Option<Vec4<f32>>>,
+	pub passes: Vec<RecordedPass>,
+	blend: Blend,
+}
+
+impl Recorder {
+	fn add(&mut self, kind: PrimKind, indices: &[u32], vertices: &[Vertex]) {
+		let blend = self.blend;
+		let pass = self.passes.last_mut().expect("primitives added outside of a pass");
+		if !matches!(pass.batches.last(), Some(batch) if batch.kind == kind && batch.blend == blend) {
+			pass.batches.push(RecordedBatch { kind, blend, indices: Vec::new(), vertices: Vec::new() });
+		}
+		let batch = pass.batches.last_mut().unwrap();
+		let base = batch.vertices.len() as u32;
+		batch.indices.extend(indices.iter().map(|&index| base + index));
+		batch.vertices.extend_from_slice(vertices);
+	}
+
+	/// Describes the recorded passes in a stable text format for comparing against golden files.
+	///
+	/// Every batch lists its primitive count, the bounds of its vertices and a hash of its vertices rounded to 1/100th.
+	pub fn summary(&self) -> String {
+		use std::fmt::Write;
+		let mut s = String::new();
+		let _ = writeln!(s, "frames {}", self.frames);
+		for color in &self.clears {
+			match color {
+				Some(c) => { let _ = writeln!(s, "clear color ({:.2}, {:.2}, {:.2}, {:.2})", c.x, c.y, c.z, c.w); }
+				None => { let _ = writeln!(s, "clear depth"); }
+			}
+		}
+		for pass in &self.passes {
+			let depth = if pass.pass.depth_test { "depth" } else { "nodepth" };
+			let _ = writeln!(s, "pass {:?} {} {}x{}", pass.pass.blend, depth, pass.pass.viewport.x, pass.pass.viewport.y);
+			for batch in &pass.batches {
+				let (min, max) = bounds(&batch.vertices);
+				let _ = writeln!(s, "\t{:?} {:?} {} prims {} verts ({:.1}, {:.1}, {:.1})..({:.1}, {:.1}, {:.1}) {:016x}",
+					batch.kind, batch.blend, batch.prim_count(), batch.vertices.len(),
+					min.x, min.y, min.z, max.x, max.y, max.z, hash_batch(batch));
+			}
+		}
+		s
+	}
+}
+
+fn bounds(vertices: &[Vertex]) -> (Vec3<f32>, Vec3<f32>) {
+	if vertices.is_empty() {
+		return (Vec3(0.0, 0.0, 0.0), Vec3(0.0, 0.0, 0.0));
+	}
+	let mut min = vertices[0].pos;
+	let mut max = vertices[0].pos;
+	for v in vertices {
+		min = Vec3(f32::min(min.x, v.pos.x), f32::min(min.y, v.pos.y), f32::min(min.z, v.pos.z));
+		max = Vec3(f32::max(max.x, v.pos.x), f32::max(max.y, v.pos.y), f32::max(max.z, v.pos.z));
+	}
+	(min, max)
+}
+
+/// FNV-1a hash of the indexed vertices, rounded so the hash is stable across platforms.
+fn hash_batch(batch: &RecordedBatch) -> u64 {
+	let mut hash = 0xcbf29ce484222325u64;
+	let mut write = |value: i64| {
+		for byte in value.to_le_bytes() {
+			hash = (hash ^ byte as u64).wrapping_mul(0x100000001b3);
+		}
+	};
+	let round = |c: f32| f32::round(c * 100.0) as i64;
+	for &index in &batch.indices {
+		let v = &batch.vertices[index as usize];
+		for c in [v.pos.x, v.pos.y, v.pos.z, v.uv.x, v.uv.y] {
+			write(round(c));
+		}
+		write(i64::from(u32::from_le_bytes(v.color)));
+	}
+	hash
+}
+
+impl PrimSink for Recorder {
+	fn add_prim(&mut self, indices: &[u32], vertices: &[Vertex]) {
+		self.add(PrimKind::Triangles, indices, vertices);
+	}
+}
+
+impl DrawBackend for Recorder {
+	fn begin(&mut self) {
+		self.frames += 1;
+	}
+	fn clear(&mut self, color: Option<Vec4<f32>>) {
+		self.clears.push(color);
+	}
+	fn begin_pass(&mut self, pass: &DrawPass) {
+		self.blend = pass.blend;
+		self.passes.push(RecordedPass { pass: *pass, batches: Vec::new() });
+	}
+	fn set_blend(&mut self, blend: Blend) {
+		self.blend = blend;
+	}
+	fn add_lines(&mut self, indices: &[u32], vertices: &[Vertex]) {
+		self.add(PrimKind::Lines, indices, vertices);
+	}
+	fn end_pass(&mut self) {}
+	fn end(&mut self) {}
+}
@@ -1,6 +1,15 @@
 use super::*;
 
-#[derive(Default)]
+/// How the scene is projected on the screen.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Projection {
+	/// Tilted perspective view.
+	#[default]
+	Perspective,
+	/// Top-down orthographic view.
+	Orthographic,
+}
+
 pub struct Camera {
 	// Object to follow with the camera
 	pub object_h: Option<ObjectHandle>,
@@ -11,16 +20,164 @@ pub struct Camera {
 	/// Eye offset from the target
 	pub eye_offset: Vec3<f32>,
 
+	/// Projection to transition to.
+	pub projection: Projection,
+	/// Blend from perspective (0.0) to orthographic (1.0), follows the projection smoothly.
+	pub ortho_blend: f32,
+	/// Zoom factor, larger values move the eye closer to the target.
+	pub zoom: f32,
+	/// Offset of the look at target in world units.
+	pub pan: Vec2<f32>,
+	/// Size of the screen the matrices were computed for.
+	pub screen_size: Vec2<i32>,
+
 	// Camera matrices
 	pub view_mat: Mat4<f32>,
 	pub proj_mat: Mat4<f32>,
 	pub view_proj_mat: Mat4<f32>,
 }
 
+impl Default for Camera {
+	fn default() -> Self {
+		Camera {
+			object_h: None,
+			target: Vec3::ZERO,
+			target_fast: Vec3::ZERO,
+			eye_offset: Vec3::ZERO,
+			projection: Projection::Perspective,
+			ortho_blend: 0.0,
+			zoom: 1.0,
+			pan: Vec2::ZERO,
+			screen_size: Vec2::ZERO,
+			view_mat: Mat4::IDENTITY,
+			proj_mat: Mat4::IDENTITY,
+			view_proj_mat: Mat4::IDENTITY,
+		}
+	}
+}
+
+const FOV_Y: f32 = 45.0;
+const NEAR: f32 = 0.1;
+const FAR: f32 = 2000.0;
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 4.0;
+
+impl Camera {
+	pub fn toggle_projection(&mut self) {
+		self.projection = match self.projection {
+			Projection::Perspective => Projection::Orthographic,
+			Projection::Orthographic => Projection::Perspective,
+		};
+	}
+	/// Multiplies the zoom factor, eg. 1.25 to zoom in and 0.8 to zoom out.
+	pub fn zoom_by(&mut self, factor: f32) {
+		self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+	}
+	/// Moves the look at target in world units.
+	pub fn pan_by(&mut self, delta: Vec2<f32>) {
+		self.pan += delta;
+	}
+	/// Pans so the ground under the pixel `from` moves under the pixel `to`, eg. to drag the view with the mouse.
+	pub fn pan_drag(&mut self, from: Vec2<i32>, to: Vec2<i32>) {
+		if let (Some(a), Some(b)) = (self.screen_to_world(from), self.screen_to_world(to)) {
+			self.pan_by((a - b).xy());
+		}
+	}
+	/// Returns the eye position in world space.
+	pub fn eye(&self) -> Vec3<f32> {
+		self.target + self.pan.vec3(0.0) + self.blended_eye_offset()
+	}
+	/// Eye offset scaled by the zoom and tilted towards the top-down view as the projection blends to orthographic.
+	fn blended_eye_offset(&self) -> Vec3<f32> {
+		let eye_offset = self.eye_offset * (1.0 / self.zoom);
+		let top_down = Vec3(0.0, 0.0, eye_offset.len());
+		eye_offset + (top_down - eye_offset) * self.ortho_blend
+	}
+
+	/// Updates the projection blend and recomputes the matrices.
+	pub fn update(&mut self, screen_size: Vec2<i32>, dt: f32) {
+		self.screen_size = screen_size;
+
+		let goal = match self.projection {
+			Projection::Perspective => 0.0,
+			Projection::Orthographic => 1.0,
+		};
+		self.ortho_blend += (goal - self.ortho_blend) * (1.0 - f32::exp(-10.0 * dt));
+		if f32::abs(goal - self.ortho_blend) < 0.001 {
+			self.ortho_blend = goal;
+		}
+
+		let (width, height) = (screen_size.x as f32, screen_size.y as f32);
+		let perspective = cvmath::Mat4::perspective_fov(cvmath::Deg(FOV_Y), width, height, NEAR, FAR, (cvmath::RH, cvmath::NO));
+
+		// The orthographic view shows as much of the ground as the perspective view does at the target
+		let eye_offset = self.blended_eye_offset();
+		let half_h = eye_offset.len() * f32::tan(FOV_Y.to_radians() * 0.5);
+		let half_w = half_h * width / height;
+		let mut orthographic = Mat4::IDENTITY;
+		orthographic.a11 = 1.0 / half_w;
+		orthographic.a22 = 1.0 / half_h;
+		orthographic.a33 = -2.0 / (FAR - NEAR);
+		orthographic.a34 = -(FAR + NEAR) / (FAR - NEAR);
+
+		self.proj_mat = lerp_mat(&perspective, &orthographic, self.ortho_blend);
+		self.view_mat = {
+			let eye = self.eye();
+			let target = self.target_fast + self.pan.vec3(0.0);
+			let up = cvmath::Vec3(0.0, -1.0, 0.0);
+			cvmath::Mat4::look_at(eye, target, up, cvmath::RH)
+		};
+		self.view_proj_mat = self.proj_mat * self.view_mat;
+	}
+
+	/// Returns the ray through the pixel in world space.
+	pub fn screen_to_ray(&self, screen_pos: Vec2<i32>) -> Ray<f32> {
+		let x = (screen_pos.x as f32 / self.screen_size.x as f32 - 0.5) * 2.0;
+		let y = (screen_pos.y as f32 / self.screen_size.y as f32 - 0.5) * -2.0;
+		let inv = self.view_proj_mat.inverse();
+		let unproject = |z: f32| {
+			let p = inv * Vec4::new(x, y, z, 1.0);
+			p.xyz() * (1.0 / p.w)
+		};
+		let near = unproject(-1.0);
+		let far = unproject(1.0);
+		Ray::new(near, (far - near).normalize())
+	}
+	/// Returns the point on the ground plane under the pixel.
+	pub fn screen_to_world(&self, screen_pos: Vec2<i32>) -> Option<Vec3<f32>> {
+		let ray = self.screen_to_ray(screen_pos);
+		let plane = Plane::new(Vec3::Z, 0.0);
+		let mut hits = [TraceHit::default(); 2];
+		if ray.trace(&plane, &mut hits) > 0 { Some(ray.at(hits[0].distance)) } else { None }
+	}
+	/// Returns the tile under the pixel.
+	pub fn screen_to_tile(&self, screen_pos: Vec2<i32>) -> Option<Vec2<i32>> {
+		let p = self.screen_to_world(screen_pos)?;
+		Some(p.xy().map(|c| f32::floor(c / 32.0) as i32))
+	}
+	/// Returns the pixel the world position projects to, or none if it is behind the camera.
+	pub fn world_to_screen(&self, pos: Vec3<f32>) -> Option<Vec2<f32>> {
+		let clip = self.view_proj_mat * Vec4::new(pos.x, pos.y, pos.z, 1.0);
+		if clip.w <= 0.0 {
+			return None;
+		}
+		let ndc = clip.xyz() * (1.0 / clip.w);
+		Some(Vec2((ndc.x + 1.0) * 0.5 * self.screen_size.x as f32, (1.0 - ndc.y) * 0.5 * self.screen_size.y as f32))
+	}
+}
+
+fn lerp_mat(a: &Mat4<f32>, b: &Mat4<f32>, t: f32) -> Mat4<f32> {
+	let lerp = |a: f32, b: f32| a + (b - a) * t;
+	Mat4 {
+		a11: lerp(a.a11, b.a11), a12: lerp(a.a12, b.a12), a13: lerp(a.a13, b.a13), a14: lerp(a.a14, b.a14),
+		a21: lerp(a.a21, b.a21), a22: lerp(a.a22, b.a22), a23: lerp(a.a23, b.a23), a24: lerp(a.a24, b.a24),
+		a31: lerp(a.a31, b.a31), a32: lerp(a.a32, b.a32), a33: lerp(a.a33, b.a33), a34: lerp(a.a34, b.a34),
+		a41: lerp(a.a41, b.a41), a42: lerp(a.a42, b.a42), a43: lerp(a.a43, b.a43), a44: lerp(a.a44, b.a44),
+	}
+}
+
 impl VisualState {
 	pub fn set_game_camera(&mut self) {
-		let size = self.resources.screen_size;
-
 		let ent_pos = if let Some(obj) = self.camera.object_h.and_then(|h| self.objects.get(h)) {
 			self.camera.eye_offset = Vec3::new(0.0, 8.0 * 32.0, 400.0);
 			obj.lerp_pos
@@ -30,17 +187,9 @@ impl VisualState {
 			self.camera.target
 		};
 
-		self.camera.target_fast = self.camera.target_fast.exp_decay(ent_pos, 25.0, 1.0 / 60.0);
-		self.camera.target = self.camera.target.exp_decay(ent_pos, 15.0, 1.0 / 60.0).with_x(self.camera.target_fast.x);
-
-		self.camera.proj_mat = cvmath::Mat4::perspective_fov(cvmath::Deg(45.0), size.x as f32, size.y as f32, 0.1, 2000.0, (cvmath::RH, cvmath::NO));
-		self.camera.view_mat = {
-			let eye = self.camera.target + self.camera.eye_offset;
-			let target = self.camera.target_fast;
-			let up = cvmath::Vec3(0.0, -1.0, 0.0);
-			cvmath::Mat4::look_at(eye, target, up, cvmath::RH)
-		};
+		self.camera.target_fast = self.camera.target_fast.exp_decay(ent_pos, 25.0, self.dt);
+		self.camera.target = self.camera.target.exp_decay(ent_pos, 15.0, self.dt).with_x(self.camera.target_fast.x);
 
-		self.camera.view_proj_mat = self.camera.proj_mat * self.camera.view_mat;
+		self.camera.update(self.resources.screen_size, self.dt);
 	}
 }
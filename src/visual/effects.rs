@@ -0,0 +1,86 @@
+use super::*;
+
+/// Short lived particle of a visual effect.
+#[derive(Clone, Debug)]
+pub struct Particle {
+	pub pos: Vec3<f32>,
+	pub vel: Vec3<f32>,
+	/// Downward acceleration in world units per second squared.
+	pub gravity: f32,
+	/// Width of the particle in world units.
+	pub size: f32,
+	/// Change of the size per second.
+	pub grow: f32,
+	/// Sprite whose center is used as the particle's texture.
+	pub sprite: Sprite,
+	pub color: [u8; 4],
+	pub age: f32,
+	/// Lifetime in seconds, the particle fades out over its lifetime.
+	pub life: f32,
+}
+
+impl Default for Particle {
+	fn default() -> Self {
+		Particle {
+			pos: Vec3::ZERO,
+			vel: Vec3::ZERO,
+			gravity: 0.0,
+			size: 4.0,
+			grow: 0.0,
+			sprite: Sprite::Floor,
+			color: [255, 255, 255, 255],
+			age: 0.0,
+			life: 1.0,
+		}
+	}
+}
+
+impl Particle {
+	/// Returns the opacity of the particle fading out over its lifetime.
+	pub fn alpha(&self) -> f32 {
+		f32::max(0.0, 1.0 - self.age / self.life)
+	}
+}
+
+/// Particles of all running effects.
+#[derive(Default)]
+pub struct Effects {
+	pub particles: Vec<Particle>,
+}
+
+/// Angle between consecutive particles of a burst, spreads them evenly without a random generator.
+const GOLDEN_ANGLE: f32 = 2.399963;
+
+impl Effects {
+	pub fn spawn(&mut self, particle: Particle) {
+		self.particles.push(particle);
+	}
+	/// Spawns particles flying outwards from the template's position.
+	///
+	/// The horizontal speed varies between half and the full speed, the template's velocity is added to every particle.
+	pub fn burst(&mut self, template: &Particle, count: i32, speed: f32) {
+		for i in 0..count {
+			let angle = i as f32 * GOLDEN_ANGLE;
+			let scale = 0.5 + 0.5 * (i as f32 * 0.618034).fract();
+			let dir = Vec3(f32::cos(angle), f32::sin(angle), 0.0) * (speed * scale);
+			self.spawn(Particle { vel: template.vel + dir, ..template.clone() });
+		}
+	}
+	pub fn update(&mut self, dt: f32) {
+		for p in &mut self.particles {
+			p.age += dt;
+			p.vel.z -= p.gravity * dt;
+			p.pos += p.vel * dt;
+			p.size = f32::max(0.0, p.size + p.grow * dt);
+			// Particles rest on the floor
+			if p.pos.z < 0.0 && p.gravity > 0.0 {
+				p.pos.z = 0.0;
+				p.vel = Vec3::ZERO;
+			}
+		}
+		self.particles.retain(|p| p.age < p.life);
+	}
+	pub fn clear(&mut self) {
+		self.particles.clear();
+	}
+}
@@ -0,0 +1,277 @@
+use super::*;
+
+/// Glyph of a bitmap font.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Glyph {
+	/// Texel rect of the glyph in the font texture, the top is aligned with the top of the line.
+	pub pos: Vec2<i32>,
+	pub size: Vec2<i32>,
+	/// Horizontal distance to the next glyph in texels.
+	pub advance: i32,
+}
+
+#[derive(serde::Deserialize)]
+struct GlyphDto {
+	rect: [i32; 4],
+	advance: i32,
+}
+
+#[derive(serde::Deserialize)]
+struct FontDto {
+	name: String,
+	texture: String,
+	line_height: i32,
+	fallback: char,
+	glyphs: HashMap<char, GlyphDto>,
+}
+
+#[derive(serde::Deserialize)]
+struct DataDto {
+	fonts: Vec<FontDto>,
+}
+
+/// Glyph metrics of a bitmap font texture.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontAtlas {
+	pub name: String,
+	/// Texture file name relative to the data directory.
+	pub texture: String,
+	/// Distance between lines in texels.
+	pub line_height: i32,
+	pub glyphs: HashMap<char, Glyph>,
+	/// Glyph drawn for characters missing from the font.
+	pub fallback: Glyph,
+}
+
+static BUILTIN_FONT: std::sync::OnceLock<FontAtlas> = std::sync::OnceLock::new();
+
+impl Default for FontAtlas {
+	fn default() -> Self {
+		BUILTIN_FONT.get_or_init(|| FontAtlas::parse_all(include_str!("../../data/data.json")).unwrap().swap_remove(0)).clone()
+	}
+}
+
+impl FontAtlas {
+	/// Parses the `fonts` section of the data file.
+	pub fn parse_all(json: &str) -> Result<Vec<FontAtlas>, String> {
+		let dto: DataDto = serde_json::from_str(json).map_err(|err| err.to_string())?;
+		if dto.fonts.is_empty() {
+			return Err(String::from("No fonts defined"));
+		}
+		dto.fonts.into_iter().map(FontAtlas::from_dto).collect()
+	}
+
+	fn from_dto(dto: FontDto) -> Result<FontAtlas, String> {
+		if dto.line_height <= 0 {
+			return Err(format!("Invalid line height for font {}", dto.name));
+		}
+
+		let mut glyphs = HashMap::with_capacity(dto.glyphs.len());
+		for (chr, glyph) in dto.glyphs {
+			let [x, y, width, height] = glyph.rect;
+			if width < 0 || height < 0 || glyph.advance < 0 {
+				return Err(format!("Invalid glyph {:?} in font {}", chr, dto.name));
+			}
+			glyphs.insert(chr, Glyph { pos: Vec2(x, y), size: Vec2(width, height), advance: glyph.advance });
+		}
+		let Some(&fallback) = glyphs.get(&dto.fallback) else {
+			return Err(format!("Missing fallback glyph {:?} in font {}", dto.fallback, dto.name));
+		};
+
+		Ok(FontAtlas {
+			name: dto.name,
+			texture: dto.texture,
+			line_height: dto.line_height,
+			glyphs,
+			fallback,
+		})
+	}
+
+	/// Checks that every glyph fits inside the font texture.
+	pub fn validate(&self, texture_size: Vec2<i32>) -> Result<(), String> {
+		for (chr, glyph) in &self.glyphs {
+			let max = glyph.pos + glyph.size;
+			if glyph.pos.x < 0 || glyph.pos.y < 0 || max.x > texture_size.x || max.y > texture_size.y {
+				return Err(format!("Glyph {:?} is outside of font {} ({}x{})", chr, self.name, texture_size.x, texture_size.y));
+			}
+		}
+		Ok(())
+	}
+
+	#[inline]
+	pub fn glyph(&self, chr: char) -> &Glyph {
+		self.glyphs.get(&chr).unwrap_or(&self.fallback)
+	}
+
+	/// Returns the width of the text in texels ignoring line breaks.
+	pub fn measure(&self, text: &str) -> i32 {
+		text.chars().map(|chr| self.glyph(chr).advance).sum()
+	}
+
+	/// Splits the text into lines at line breaks and where a line would exceed the max width in texels.
+	///
+	/// Lines are broken between words where possible, words wider than a line are broken between characters.
+	pub fn wrap(&self, text: &str, max_width: Option<i32>) -> Vec<String> {
+		let mut lines = Vec::new();
+		for paragraph in text.split('\n') {
+			let Some(max_width) = max_width else {
+				lines.push(String::from(paragraph));
+				continue;
+			};
+			let space = self.glyph(' ').advance;
+			let mut line = String::new();
+			let mut line_width = 0;
+			for word in paragraph.split(' ').filter(|word| !word.is_empty()) {
+				let word_width = self.measure(word);
+				if !line.is_empty() {
+					if line_width + space + word_width <= max_width {
+						line.push(' ');
+						line.push_str(word);
+						line_width += space + word_width;
+						continue;
+					}
+					lines.push(std::mem::take(&mut line));
+					line_width = 0;
+				}
+				for chr in word.chars() {
+					let advance = self.glyph(chr).advance;
+					if !line.is_empty() && line_width + advance > max_width {
+						lines.push(std::mem::take(&mut line));
+						line_width = 0;
+					}
+					line.push(chr);
+					line_width += advance;
+				}
+			}
+			lines.push(line);
+		}
+		lines
+	}
+
+	/// Positions the glyphs of the text relative to the top left corner of the text block.
+	pub fn layout(&self, text: &str, style: &TextStyle) -> TextLayout {
+		let max_width = style.max_width.map(|width| f32::floor(width / style.scale) as i32);
+		let lines = self.wrap(text, max_width);
+		let widths = lines.iter().map(|line| self.measure(line)).collect::<Vec<_>>();
+		let block_width = widths.iter().cloned().max().unwrap_or(0);
+
+		let mut quads = Vec::new();
+		for (i, line) in lines.iter().enumerate() {
+			let mut x = match style.align {
+				TextAlign::Left => 0,
+				TextAlign::Center => (block_width - widths[i]) / 2,
+				TextAlign::Right => block_width - widths[i],
+			};
+			let y = i as i32 * self.line_height;
+			for chr in line.chars() {
+				let glyph = self.glyph(chr);
+				if glyph.size.x > 0 && glyph.size.y > 0 {
+					quads.push(GlyphQuad {
+						pos: Vec2(x, y).map(|c| c as f32 * style.scale),
+						size: glyph.size.map(|c| c as f32 * style.scale),
+						uv: glyph.pos.map(|c| c as f32),
+						uv_size: glyph.size.map(|c| c as f32),
+					});
+				}
+				x += glyph.advance;
+			}
+		}
+
+		let size = Vec2(block_width, lines.len() as i32 * self.line_height).map(|c| c as f32 * style.scale);
+		TextLayout { quads, size }
+	}
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum TextAlign {
+	#[default]
+	Left,
+	Center,
+	Right,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TextStyle {
+	/// Size of a font texel in screen pixels or world units.
+	pub scale: f32,
+	pub color: [u8; 4],
+	/// Alignment of the lines within the text block.
+	pub align: TextAlign,
+	/// Lines longer than this are wrapped.
+	pub max_width: Option<f32>,
+}
+
+impl Default for TextStyle {
+	fn default() -> Self {
+		TextStyle {
+			scale: 1.0,
+			color: [255, 255, 255, 255],
+			align: TextAlign::Left,
+			max_width: None,
+		}
+	}
+}
+
+/// Placed glyph of a text layout.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct GlyphQuad {
+	/// Top left corner relative to the text block.
+	pub pos: Vec2<f32>,
+	pub size: Vec2<f32>,
+	pub uv: Vec2<f32>,
+	pub uv_size: Vec2<f32>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TextLayout {
+	pub quads: Vec<GlyphQuad>,
+	/// Size of the text block.
+	pub size: Vec2<f32>,
+}
+
+/// Draws the text in screen space with the top left corner of the text block at the position.
+///
+/// The pass is expected to map pixels with the y axis pointing down and use the font texture.
+pub fn draw_text(cv: &mut impl PrimSink, font: &FontAtlas, pos: Vec2<f32>, text: &str, style: &TextStyle) {
+	let layout = font.layout(text, style);
+	for quad in &layout.quads {
+		let min = pos + quad.pos;
+		let max = min + quad.size;
+		add_glyph(cv, quad, [
+			Vec3(min.x, min.y, 0.0),
+			Vec3(min.x, max.y, 0.0),
+			Vec3(max.x, max.y, 0.0),
+			Vec3(max.x, min.y, 0.0),
+		], style.color);
+	}
+}
+
+/// Draws the text standing upright in world space with the bottom center of the text block at the position.
+pub fn draw_label(cv: &mut impl PrimSink, font: &FontAtlas, pos: Vec3<f32>, text: &str, style: &TextStyle) {
+	let layout = font.layout(text, style);
+	let left = pos.x - layout.size.x * 0.5;
+	let top = pos.z + layout.size.y;
+	for quad in &layout.quads {
+		let (x1, x2) = (left + quad.pos.x, left + quad.pos.x + quad.size.x);
+		let (z1, z2) = (top - quad.pos.y, top - quad.pos.y - quad.size.y);
+		add_glyph(cv, quad, [
+			Vec3(x1, pos.y, z1),
+			Vec3(x1, pos.y, z2),
+			Vec3(x2, pos.y, z2),
+			Vec3(x2, pos.y, z1),
+		], style.color);
+	}
+}
+
+/// Corners are top left, bottom left, bottom right and top right.
+fn add_glyph(cv: &mut impl PrimSink, quad: &GlyphQuad, corners: [Vec3<f32>; 4], color: [u8; 4]) {
+	let Vec2 { x: u, y: v } = quad.uv;
+	let Vec2 { x: us, y: vs } = quad.uv_size;
+
+	cv.add_prim(&render::QUAD_INDICES, &[
+		render::Vertex { pos: corners[0], uv: Vec2(u, v), color },
+		render::Vertex { pos: corners[1], uv: Vec2(u, v + vs), color },
+		render::Vertex { pos: corners[2], uv: Vec2(u + us, v + vs), color },
+		render::Vertex { pos: corners[3], uv: Vec2(u + us, v), color },
+	]);
+}
@@ -0,0 +1,47 @@
+use super::*;
+
+/// View frustum of a camera for culling, the planes' normals point inwards.
+#[derive(Copy, Clone, Debug)]
+pub struct Frustum {
+	planes: [Vec4<f32>; 6],
+}
+
+impl Frustum {
+	/// Extracts the clipping planes from the view projection matrix.
+	pub fn new(m: &Mat4<f32>) -> Frustum {
+		let row1 = Vec4(m.a11, m.a12, m.a13, m.a14);
+		let row2 = Vec4(m.a21, m.a22, m.a23, m.a24);
+		let row3 = Vec4(m.a31, m.a32, m.a33, m.a34);
+		let row4 = Vec4(m.a41, m.a42, m.a43, m.a44);
+		Frustum {
+			planes: [
+				row4 + row1,
+				row4 - row1,
+				row4 + row2,
+				row4 - row2,
+				row4 + row3,
+				row4 - row3,
+			],
+		}
+	}
+
+	/// Returns if any part of the axis aligned box may be visible.
+	pub fn contains_box(&self, min: Vec3<f32>, max: Vec3<f32>) -> bool {
+		for plane in &self.planes {
+			// Test the corner furthest along the plane normal
+			let x = if plane.x >= 0.0 { max.x } else { min.x };
+			let y = if plane.y >= 0.0 { max.y } else { min.y };
+			let z = if plane.z >= 0.0 { max.z } else { min.z };
+			if plane.x * x + plane.y * y + plane.z * z + plane.w < 0.0 {
+				return false;
+			}
+		}
+		true
+	}
+}
+
+impl Camera {
+	pub fn frustum(&self) -> Frustum {
+		Frustum::new(&self.view_proj_mat)
+	}
+}
@@ -66,7 +66,7 @@ pub fn entity_step(ctx: &mut VisualState, handle: core::EntityHandle) {
 		src: ent.pos - ent.step_dir.unwrap().to_vec(),
 		dest: ent.pos,
 		move_time: ctx.time,
-		move_spd: ent.step_spd as f32 / 60.0,
+		move_spd: ticks_to_time(ent.step_spd),
 	});
 }
 
@@ -158,7 +158,7 @@ pub fn create_toggle_wall(ctx: &mut VisualState, pos: Vec2<i32>) {
 	ctx.objects.insert(obj);
 }
 
-fn model_for_ent(ent: &core::Entity) -> Model {
+pub fn model_for_ent(ent: &core::Entity) -> Model {
 	match ent.kind {
 		core::EntityKind::Block => Model::Wall,
 		core::EntityKind::Tank => Model::ReallyFlatSprite,
@@ -168,11 +168,15 @@ fn model_for_ent(ent: &core::Entity) -> Model {
 	}
 }
 
-fn sprite_for_ent(ent: &core::Entity, pl: &core::PlayerState) -> Sprite {
-	match ent.kind {
+pub fn sprite_for_ent(ent: &core::Entity, pl: &core::PlayerState) -> Sprite {
+	sprite_for_kind(ent.kind, ent.face_dir, pl)
+}
+
+pub fn sprite_for_kind(kind: core::EntityKind, face_dir: Option<core::Dir>, pl: &core::PlayerState) -> Sprite {
+	match kind {
 		core::EntityKind::Player => match pl.action {
 			core::PlayerAction::Walk | core::PlayerAction::Push | core::PlayerAction::Skate | core::PlayerAction::Suction | core::PlayerAction::Slide =>
-				match ent.face_dir {
+				match face_dir {
 					Some(core::Dir::Up) => Sprite::PlayerWalkUp,
 					Some(core::Dir::Down) => Sprite::PlayerWalkDown,
 					Some(core::Dir::Left) => Sprite::PlayerWalkLeft,
@@ -180,7 +184,7 @@ fn sprite_for_ent(ent: &core::Entity, pl: &core::PlayerState) -> Sprite {
 					_ => Sprite::PlayerWalkNeutral,
 				},
 			core::PlayerAction::Win => Sprite::PlayerCheer,
-			core::PlayerAction::Swim => match ent.face_dir {
+			core::PlayerAction::Swim => match face_dir {
 				Some(core::Dir::Up) => Sprite::PlayerSwimUp,
 				Some(core::Dir::Down) => Sprite::PlayerSwimDown,
 				Some(core::Dir::Left) => Sprite::PlayerSwimLeft,
@@ -203,14 +207,14 @@ fn sprite_for_ent(ent: &core::Entity, pl: &core::PlayerState) -> Sprite {
 		core::EntityKind::GreenKey => Sprite::GreenKey,
 		core::EntityKind::YellowKey => Sprite::YellowKey,
 		core::EntityKind::Thief => Sprite::Thief,
-		core::EntityKind::Bug => match ent.face_dir {
+		core::EntityKind::Bug => match face_dir {
 			Some(core::Dir::Up) => Sprite::BugUp,
 			Some(core::Dir::Down) => Sprite::BugDown,
 			Some(core::Dir::Left) => Sprite::BugLeft,
 			Some(core::Dir::Right) => Sprite::BugRight,
 			_ => Sprite::BugUp,
 		},
-		core::EntityKind::Tank => match ent.face_dir {
+		core::EntityKind::Tank => match face_dir {
 			Some(core::Dir::Up) => Sprite::TankUp,
 			Some(core::Dir::Down) => Sprite::TankDown,
 			Some(core::Dir::Left) => Sprite::TankLeft,
@@ -219,19 +223,19 @@ fn sprite_for_ent(ent: &core::Entity, pl: &core::PlayerState) -> Sprite {
 		},
 		core::EntityKind::PinkBall => Sprite::PinkBall,
 		core::EntityKind::FireBall => Sprite::FireBall,
-		core::EntityKind::Glider => match ent.face_dir {
+		core::EntityKind::Glider => match face_dir {
 			Some(core::Dir::Up) => Sprite::GliderUp,
 			Some(core::Dir::Down) => Sprite::GliderDown,
 			Some(core::Dir::Left) => Sprite::GliderLeft,
 			Some(core::Dir::Right) => Sprite::GliderRight,
 			_ => Sprite::GliderUp,
 		},
-		core::EntityKind::Walker => match ent.face_dir {
+		core::EntityKind::Walker => match face_dir {
 			Some(core::Dir::Up) | Some(core::Dir::Down) => Sprite::WalkerUpDown,
 			Some(core::Dir::Left) | Some(core::Dir::Right) => Sprite::WalkerLeftRight,
 			_ => Sprite::WalkerUpDown,
 		},
-		core::EntityKind::Teeth => match ent.face_dir {
+		core::EntityKind::Teeth => match face_dir {
 			Some(core::Dir::Up) => Sprite::TeethUp,
 			Some(core::Dir::Down) => Sprite::TeethDown,
 			Some(core::Dir::Left) => Sprite::TeethLeft,
@@ -239,7 +243,7 @@ fn sprite_for_ent(ent: &core::Entity, pl: &core::PlayerState) -> Sprite {
 			_ => Sprite::TeethUp,
 		},
 		core::EntityKind::Blob => Sprite::Blob,
-		core::EntityKind::Paramecium => match ent.face_dir {
+		core::EntityKind::Paramecium => match face_dir {
 			Some(core::Dir::Up) | Some(core::Dir::Down) => Sprite::ParameciumUpDown,
 			Some(core::Dir::Left) | Some(core::Dir::Right) => Sprite::ParameciumLeftRight,
 			_ => Sprite::ParameciumUpDown,
@@ -365,3 +369,51 @@ pub fn toggle_walls(ctx: &mut VisualState) {
 		}
 	}
 }
+
+/// Returns the center of the entity's object, the object outlives the entity until its removal is synced.
+fn object_center(ctx: &VisualState, handle: core::EntityHandle) -> Option<Vec3<f32>> {
+	let &obj_handle = ctx.objects.lookup.get(&handle)?;
+	let obj = ctx.objects.get(obj_handle)?;
+	Some(obj.pos + Vec3(16.0, 16.0, 0.0))
+}
+
+fn tile_center(pos: Vec2<i32>) -> Vec3<f32> {
+	pos.map(|c| c as f32 * 32.0 + 16.0).vec3(0.0)
+}
+
+pub fn bomb_explode(ctx: &mut VisualState, handle: core::EntityHandle) {
+	let Some(pos) = object_center(ctx, handle) else { return };
+
+	// Flash of the blast
+	ctx.effects.spawn(Particle { pos, size: 24.0, grow: 96.0, sprite: Sprite::Fire, color: [255, 200, 80, 255], life: 0.25, ..Particle::default() });
+	// Debris flying outwards and falling back down
+	ctx.effects.burst(&Particle { pos: pos.with_z(8.0), vel: Vec3(0.0, 0.0, 150.0), gravity: 600.0, size: 5.0, sprite: Sprite::Bomb, color: [160, 160, 160, 255], life: 0.8, ..Particle::default() }, 16, 120.0);
+}
+
+pub fn entity_drown(ctx: &mut VisualState, handle: core::EntityHandle) {
+	let Some(pos) = object_center(ctx, handle) else { return };
+
+	ctx.effects.burst(&Particle { pos, vel: Vec3(0.0, 0.0, 120.0), gravity: 500.0, size: 4.0, sprite: Sprite::Water, color: [200, 230, 255, 255], life: 0.6, ..Particle::default() }, 12, 60.0);
+}
+
+pub fn socket_filled(ctx: &mut VisualState, pos: Vec2<i32>) {
+	// Sparkles rising from the opened socket
+	ctx.effects.burst(&Particle { pos: tile_center(pos), vel: Vec3(0.0, 0.0, 60.0), size: 3.0, sprite: Sprite::Chip, color: [255, 255, 128, 255], life: 0.7, ..Particle::default() }, 10, 40.0);
+}
+
+pub fn items_thief(ctx: &mut VisualState, player: core::EntityHandle) {
+	let Some(pos) = object_center(ctx, player) else { return };
+
+	// Flash around the player as the boots are taken
+	ctx.effects.spawn(Particle { pos, size: 32.0, grow: 64.0, sprite: Sprite::Floor, color: [255, 255, 255, 200], life: 0.3, ..Particle::default() });
+	ctx.effects.burst(&Particle { pos: pos.with_z(12.0), size: 4.0, sprite: Sprite::Thief, color: [255, 255, 255, 255], life: 0.4, ..Particle::default() }, 8, 80.0);
+}
+
+pub fn blue_wall_bumped(ctx: &mut VisualState, pos: Vec2<i32>) {
+	ctx.effects.burst(&Particle { pos: tile_center(pos).with_z(16.0), size: 3.0, sprite: Sprite::BlueWall, color: [128, 160, 255, 255], life: 0.3, ..Particle::default() }, 6, 40.0);
+}
+
+pub fn player_hint(ctx: &mut VisualState, pos: Vec2<i32>) {
+	ctx.hud.hint_pos = Some(pos);
+	ctx.effects.burst(&Particle { pos: tile_center(pos), vel: Vec3(0.0, 0.0, 40.0), size: 3.0, sprite: Sprite::Hint, color: [255, 255, 255, 255], life: 1.0, ..Particle::default() }, 5, 10.0);
+}
@@ -0,0 +1,172 @@
+use super::*;
+use std::{fs, path::{Path, PathBuf}, time::SystemTime};
+
+/// File watched for changes by its modification time.
+#[derive(Clone, Debug, Default)]
+pub struct WatchedFile {
+	pub path: PathBuf,
+	mtime: Option<SystemTime>,
+}
+
+impl WatchedFile {
+	pub fn new(path: PathBuf) -> WatchedFile {
+		let mtime = modified(&path);
+		WatchedFile { path, mtime }
+	}
+	/// Returns if the file was modified since it was last polled.
+	///
+	/// Missing files are not reported as changed, eg. while an editor is saving them.
+	pub fn poll(&mut self) -> bool {
+		let mtime = modified(&self.path);
+		if mtime.is_none() || mtime == self.mtime {
+			return false;
+		}
+		self.mtime = mtime;
+		true
+	}
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+	fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Source files of the resources.
+#[derive(Clone, Debug, Default)]
+pub struct ResourceFiles {
+	/// Directory containing the data file, the shaders and the textures.
+	pub data_dir: PathBuf,
+	pub data: WatchedFile,
+	pub vertex_shader: WatchedFile,
+	pub fragment_shader: WatchedFile,
+	pub tileset: WatchedFile,
+}
+
+/// Outcome of a hot reload.
+#[derive(Clone, Debug, Default)]
+pub struct HotReload {
+	pub shader: bool,
+	pub tileset: bool,
+	/// Contents of the data file if it changed, the game data is reloaded from it by the caller.
+	pub data: Option<String>,
+	/// Resources that failed to reload, these keep their old version.
+	pub errors: Vec<String>,
+}
+
+impl Resources {
+	/// Watches the source files of the resources in the data directory for changes.
+	pub fn watch(&mut self, data_dir: &str) {
+		let data_dir = PathBuf::from(data_dir);
+		self.watch = Some(ResourceFiles {
+			data: WatchedFile::new(data_dir.join("data.json")),
+			vertex_shader: WatchedFile::new(data_dir.join("standard.vs.glsl")),
+			fragment_shader: WatchedFile::new(data_dir.join("standard.fs.glsl")),
+			tileset: WatchedFile::new(data_dir.join(&self.atlas.texture)),
+			data_dir,
+		});
+	}
+
+	/// Reloads the resources whose source files changed since the last call.
+	pub fn hot_reload(&mut self, g: &mut shade::Graphics) -> HotReload {
+		let mut result = HotReload::default();
+		let Some(mut files) = self.watch.take() else { return result };
+
+		// Poll both shaders so an edit to one does not report the other later
+		let vs_changed = files.vertex_shader.poll();
+		let fs_changed = files.fragment_shader.poll();
+		if vs_changed || fs_changed {
+			let read = |file: &WatchedFile| fs::read_to_string(&file.path).map_err(|err| format!("{}: {}", file.path.display(), err));
+			match read(&files.vertex_shader).and_then(|vs_src| Ok((vs_src, read(&files.fragment_shader)?))) {
+				Ok((vs_src, fs_src)) => match self.load_shader(g, &vs_src, &fs_src) {
+					Ok(()) => result.shader = true,
+					Err(err) => {
+						// The shader that did not change compiled before so the error is in the changed ones
+						let changed = [(vs_changed, &files.vertex_shader), (fs_changed, &files.fragment_shader)];
+						let paths = changed.iter().filter(|(changed, _)| *changed).map(|(_, file)| file.path.display().to_string()).collect::<Vec<_>>();
+						result.errors.push(format!("{}: {}", paths.join(", "), err));
+					}
+				},
+				Err(err) => result.errors.push(err),
+			}
+		}
+
+		// An atlas with a renamed texture is only used once its texture is loaded
+		let mut renamed_atlas = None;
+		if files.data.poll() {
+			match fs::read_to_string(&files.data.path).map_err(|err| err.to_string()).and_then(|json| Ok((self.reload_data(&json)?, json))) {
+				Ok((atlas, json)) => {
+					renamed_atlas = atlas;
+					result.data = Some(json);
+				}
+				Err(err) => result.errors.push(format!("{}: {}", files.data.path.display(), err)),
+			}
+		}
+
+		// The tileset follows the current atlas, eg. after switching skins
+		let atlas = renamed_atlas.as_ref().unwrap_or(&self.atlas);
+		let tileset_path = files.data_dir.join(&atlas.texture);
+		if files.tileset.path != tileset_path {
+			files.tileset = WatchedFile::new(tileset_path);
+		}
+		if files.tileset.poll() || renamed_atlas.is_some() {
+			let path = files.tileset.path.display().to_string();
+			match Skin::load(g, renamed_atlas.unwrap_or_else(|| self.atlas.clone()), &path) {
+				Ok(skin) => {
+					// The reloaded skin replaces the current skin, its texture is released unless another skin uses it
+					if let Some(old) = self.skins.get(self.skin_index).map(|old| old.tileset) {
+						self.skins[self.skin_index] = skin.clone();
+						if self.skins.iter().all(|skin| skin.tileset != old) {
+							let _ = g.texture2d_free(old, shade::FreeMode::Delete);
+						}
+						self.set_skin(&skin);
+					}
+					else {
+						self.add_skin(skin);
+					}
+					result.tileset = true;
+				}
+				Err(err) => result.errors.push(err),
+			}
+		}
+
+		self.watch = Some(files);
+		result
+	}
+
+	/// Updates the sprite atlas and font metrics from the data file, nothing is changed if the data is invalid.
+	///
+	/// Returns the atlas instead if its texture was renamed as it cannot be used before the texture is loaded.
+	pub fn reload_data(&mut self, json: &str) -> Result<Option<SpriteAtlas>, String> {
+		let atlases = SpriteAtlas::parse_all(json)?;
+		let atlas = atlases.iter().find(|atlas| atlas.name == self.atlas.name).unwrap_or(&atlases[0]).clone();
+		let fonts = FontAtlas::parse_all(json)?;
+		let font = fonts.iter().find(|font| font.name == self.font_atlas.name).unwrap_or(&fonts[0]).clone();
+		font.validate(self.font_size)?;
+		if atlas.texture != self.atlas.texture {
+			self.font_atlas = font;
+			return Ok(Some(atlas));
+		}
+		atlas.validate(self.tileset_size)?;
+		if let Some(skin) = self.skins.get_mut(self.skin_index) {
+			skin.atlas.clone_from(&atlas);
+		}
+		self.atlas = atlas;
+		self.font_atlas = font;
+		Ok(None)
+	}
+}
+
+impl VisualState {
+	/// Reloads the changed resources and game data, returns the errors of the resources that failed to reload.
+	pub fn hot_reload(&mut self, g: &mut shade::Graphics) -> Vec<String> {
+		let mut result = self.resources.hot_reload(g);
+		if let Some(json) = &result.data {
+			if let Err(err) = self.load_data(json) {
+				result.errors.push(err);
+			}
+		}
+		if result.tileset || result.data.is_some() {
+			self.terrain_cache.clear();
+		}
+		result.errors
+	}
+}
@@ -0,0 +1,137 @@
+use super::*;
+
+/// Screen-space overlay with the player's inventory and level info.
+#[derive(Default)]
+pub struct Hud {
+	/// Hides the overlay, eg. while editing.
+	pub hidden: bool,
+	/// Hint tile the player stands on, the hint panel is shown until the player leaves it.
+	pub hint_pos: Option<Vec2<i32>>,
+}
+
+const TEXT_STYLE: TextStyle = TextStyle { scale: 2.0, color: [0, 0, 0, 255], align: TextAlign::Left, max_width: None };
+const ICON_SIZE: f32 = 32.0;
+const MARGIN: f32 = 8.0;
+
+impl VisualState {
+	pub fn draw_hud(&mut self, g: &mut impl DrawBackend) {
+		if self.hud.hidden {
+			return;
+		}
+
+		// The hint panel disappears when the player leaves the hint tile
+		let player_pos = self.game.ents.get(self.game.ps.entity).map(|ent| ent.pos);
+		if self.hud.hint_pos.is_some() && self.hud.hint_pos != player_pos {
+			self.hud.hint_pos = None;
+		}
+
+		let screen_size = self.resources.screen_size;
+
+		// Map pixels to clip space with the y axis pointing down
+		let mut transform = Mat4::IDENTITY;
+		transform.a11 = 2.0 / screen_size.x as f32;
+		transform.a14 = -1.0;
+		transform.a22 = -2.0 / screen_size.y as f32;
+		transform.a24 = 1.0;
+		transform.a33 = 0.0;
+
+		// Panels and icons use the tileset, text is drawn on top with the font texture
+		let mut cv = Mesh::default();
+		let mut tv = Mesh::default();
+
+		let atlas = &self.resources.atlas;
+		let font = &self.resources.font_atlas;
+		let field = &self.game.field;
+		let ps = &self.game.ps;
+		let panel = atlas.frame(Sprite::Floor, 0.0);
+		let width = screen_size.x as f32;
+		let line_h = font.line_height as f32 * TEXT_STYLE.scale;
+		// Offset to center a line of text next to an icon
+		let text_y = (ICON_SIZE - line_h) * 0.5;
+
+		// Level name
+		let name = font.layout(&field.name, &TEXT_STYLE);
+		draw_panel(&mut cv, &panel, Vec2(MARGIN, MARGIN), Vec2(MARGIN * 3.0, MARGIN * 3.0) + name.size);
+		draw_text(&mut tv, font, Vec2(MARGIN * 2.0, MARGIN * 2.0), &field.name, &TEXT_STYLE);
+
+		// Chips left and time left
+		let column_w = ICON_SIZE * 4.0 + MARGIN * 2.0;
+		let x = width - MARGIN - column_w;
+		let mut y = MARGIN;
+		draw_panel(&mut cv, &panel, Vec2(x, y), Vec2(x + column_w, y + MARGIN * 2.0 + ICON_SIZE * 4.0));
+		y += MARGIN;
+
+		let chips_left = i32::max(0, field.chips - ps.chips);
+		render::draw_sprite(&mut cv, atlas, Sprite::Chip, Vec3(x + MARGIN, y, 0.0));
+		draw_text(&mut tv, font, Vec2(x + MARGIN + ICON_SIZE + MARGIN, y + text_y), &format!("{}", chips_left), &TEXT_STYLE);
+		y += ICON_SIZE;
+
+		let time_left = if field.time > 0 { format!("Time {}", i32::max(0, field.time - ticks_to_time(self.game.time) as i32)) } else { String::from("Time ---") };
+		draw_text(&mut tv, font, Vec2(x + MARGIN, y + text_y), &time_left, &TEXT_STYLE);
+		y += ICON_SIZE;
+
+		// Keys with their count and the boots held
+		let keys = [Sprite::BlueKey, Sprite::RedKey, Sprite::GreenKey, Sprite::YellowKey];
+		for (i, &sprite) in keys.iter().enumerate() {
+			let pos = Vec2(x + MARGIN + i as f32 * ICON_SIZE, y);
+			if ps.keys[i] > 0 {
+				render::draw_sprite(&mut cv, atlas, sprite, pos.vec3(0.0));
+			}
+			if ps.keys[i] > 1 {
+				let count = format!("{}", ps.keys[i]);
+				let count_w = font.layout(&count, &TEXT_STYLE).size.x;
+				draw_text(&mut tv, font, pos + Vec2(ICON_SIZE - count_w, ICON_SIZE - line_h), &count, &TEXT_STYLE);
+			}
+		}
+		y += ICON_SIZE;
+
+		let boots = [
+			(ps.flippers, Sprite::PowerFlippers),
+			(ps.fire_boots, Sprite::PowerFireBoots),
+			(ps.ice_skates, Sprite::PowerIceSkates),
+			(ps.suction_boots, Sprite::PowerSuctionBoots),
+		];
+		for (i, &(held, sprite)) in boots.iter().enumerate() {
+			if held {
+				render::draw_sprite(&mut cv, atlas, sprite, Vec3(x + MARGIN + i as f32 * ICON_SIZE, y, 0.0));
+			}
+		}
+
+		// Hint panel at the bottom of the screen
+		if self.hud.hint_pos.is_some() && !field.hint.is_empty() {
+			let panel_w = f32::min(width - MARGIN * 2.0, 480.0);
+			let style = TextStyle { max_width: Some(panel_w - MARGIN * 2.0), ..TEXT_STYLE };
+			let hint = font.layout(&field.hint, &style);
+			let panel_h = hint.size.y + MARGIN * 2.0;
+			let min = Vec2((width - panel_w) * 0.5, screen_size.y as f32 - MARGIN - panel_h);
+			draw_panel(&mut cv, &panel, min, min + Vec2(panel_w, panel_h));
+			draw_text(&mut tv, font, min + Vec2(MARGIN, MARGIN), &field.hint, &style);
+		}
+
+		let pass = DrawPass {
+			shader: self.resources.shader,
+			uniform: self.resources.tileset_uniform(transform),
+			depth_test: false,
+			blend: Blend::Alpha,
+			viewport: screen_size,
+		};
+		g.begin_pass(&pass);
+		cv.draw(g);
+		g.end_pass();
+		g.begin_pass(&DrawPass { uniform: self.resources.font_uniform(transform), ..pass });
+		tv.draw(g);
+		g.end_pass();
+	}
+}
+
+/// Draws the panel background stretched from the sprite.
+pub fn draw_panel(cv: &mut impl PrimSink, gfx: &SpriteFrame, min: Vec2<f32>, max: Vec2<f32>) {
+	let (uv_min, uv_max) = (gfx.uv, gfx.uv + gfx.uv_size);
+	let color = [255, 255, 255, 224];
+	cv.add_prim(&render::QUAD_INDICES, &[
+		render::Vertex { pos: Vec3(min.x, min.y, 0.0), uv: Vec2(uv_min.x, uv_min.y), color },
+		render::Vertex { pos: Vec3(min.x, max.y, 0.0), uv: Vec2(uv_min.x, uv_max.y), color },
+		render::Vertex { pos: Vec3(max.x, max.y, 0.0), uv: Vec2(uv_max.x, uv_max.y), color },
+		render::Vertex { pos: Vec3(max.x, min.y, 0.0), uv: Vec2(uv_max.x, uv_min.y), color },
+	]);
+}
@@ -2,28 +2,44 @@ use std::collections::HashMap;
 use cvmath::*;
 use crate::core;
 
+mod backend;
 mod camera;
+mod effects;
+mod font;
+mod frustum;
 mod model;
 mod handlers;
+mod hotreload;
+mod hud;
 mod object;
 mod objectmap;
 mod sprite;
+mod terraincache;
+mod timestep;
 mod visualstate;
 mod resources;
 pub mod render;
 mod tile;
 
+pub use self::backend::*;
 pub use self::camera::*;
+pub use self::effects::*;
+pub use self::font::*;
+pub use self::frustum::*;
 pub use self::model::*;
 pub use self::handlers::*;
+pub use self::hotreload::*;
+pub use self::hud::*;
 pub use self::object::*;
 pub use self::objectmap::*;
 pub use self::sprite::*;
+pub use self::terraincache::*;
+pub use self::timestep::*;
 pub use self::visualstate::*;
 pub use self::resources::*;
 pub use self::render::*;
 pub use self::tile::*;
 
 fn ticks_to_time(ticks: i32) -> f32 {
-	ticks as f32 / 60.0
+	ticks as f32 / TICK_RATE
 }
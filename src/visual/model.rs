@@ -1,3 +1,4 @@
+use super::*;
 
 #[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -25,3 +26,171 @@ pub enum Animation {
 	Fall,
 	Raise,
 }
+
+/// Mesh configuration of an entity kind.
+#[derive(serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeshDef {
+	pub kind: core::EntityKind,
+	/// STL file name relative to the data directory.
+	pub mesh: String,
+	/// World units per mesh unit.
+	#[serde(default = "default_scale")]
+	pub scale: f32,
+	/// Turns the mesh to the entity's facing direction, meshes face up (negative y) by default.
+	#[serde(default)]
+	pub rotate: bool,
+	/// Sprite projected on the mesh from above, the entity's sprite if none.
+	///
+	/// Rotated meshes should use a sprite facing up as the projected sprite rotates with the mesh.
+	#[serde(default)]
+	pub sprite: Option<Sprite>,
+	#[serde(default = "default_color")]
+	pub color: [u8; 4],
+}
+
+fn default_scale() -> f32 {
+	1.0
+}
+
+fn default_color() -> [u8; 4] {
+	[255, 255, 255, 255]
+}
+
+#[derive(serde::Deserialize)]
+struct ModelsDto {
+	#[serde(default)]
+	models: Vec<MeshDef>,
+}
+
+impl MeshDef {
+	/// Parses the `models` section of the data file.
+	pub fn parse_all(json: &str) -> Result<Vec<MeshDef>, String> {
+		let dto: ModelsDto = serde_json::from_str(json).map_err(|err| err.to_string())?;
+		for def in &dto.models {
+			if def.scale.is_nan() || def.scale <= 0.0 {
+				return Err(format!("Invalid scale for the {:?} mesh", def.kind));
+			}
+		}
+		Ok(dto.models)
+	}
+}
+
+/// Triangle mesh drawn in place of an entity's sprite.
+#[derive(Clone, Debug, Default)]
+pub struct MeshModel {
+	/// Triangle corners centered on the tile with the bottom at zero.
+	pub positions: Vec<Vec3<f32>>,
+	/// Face normal of every corner.
+	pub normals: Vec<Vec3<f32>>,
+	/// Texture coordinates as a fraction of the sprite, projected from above.
+	pub uvs: Vec<Vec2<f32>>,
+	pub indices: Vec<u32>,
+}
+
+impl MeshModel {
+	/// Parses a binary or ASCII STL file.
+	pub fn parse_stl(data: &[u8], scale: f32) -> Result<MeshModel, String> {
+		let triangles = if is_binary_stl(data) { parse_binary_stl(data) } else { parse_ascii_stl(data)? };
+		let mesh = MeshModel::from_triangles(&triangles, scale);
+		if mesh.indices.is_empty() {
+			return Err(String::from("STL file has no triangles"));
+		}
+		Ok(mesh)
+	}
+
+	/// Builds the mesh from counter-clockwise triangles.
+	///
+	/// Degenerate triangles have no normal and are skipped.
+	pub fn from_triangles(triangles: &[[Vec3<f32>; 3]], scale: f32) -> MeshModel {
+		let triangles = triangles.iter().filter_map(|triangle| {
+			// The winding is more reliable than the normals stored in STL files
+			let normal = (triangle[1] - triangle[0]).cross(triangle[2] - triangle[0]);
+			let len = normal.len();
+			if len > 0.0 && len.is_finite() { Some((triangle, normal * (1.0 / len))) } else { None }
+		}).collect::<Vec<_>>();
+
+		let mut min = Vec3(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+		let mut max = Vec3(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+		for &p in triangles.iter().flat_map(|(triangle, _)| triangle.iter()) {
+			min = Vec3(f32::min(min.x, p.x), f32::min(min.y, p.y), f32::min(min.z, p.z));
+			max = Vec3(f32::max(max.x, p.x), f32::max(max.y, p.y), f32::max(max.z, p.z));
+		}
+		let origin = Vec3((min.x + max.x) * 0.5, (min.y + max.y) * 0.5, min.z);
+		let size = Vec2(f32::max(max.x - min.x, f32::EPSILON), f32::max(max.y - min.y, f32::EPSILON));
+
+		let mut mesh = MeshModel::default();
+		for (triangle, normal) in triangles {
+			for &p in triangle {
+				mesh.indices.push(mesh.positions.len() as u32);
+				mesh.positions.push((p - origin) * scale);
+				mesh.normals.push(normal);
+				mesh.uvs.push(Vec2((p.x - min.x) / size.x, (p.y - min.y) / size.y));
+			}
+		}
+		mesh
+	}
+}
+
+fn is_binary_stl(data: &[u8]) -> bool {
+	if data.len() < 84 {
+		return false;
+	}
+	let count = u32::from_le_bytes([data[80], data[81], data[82], data[83]]) as usize;
+	data.len() == 84 + count * 50
+}
+
+fn parse_binary_stl(data: &[u8]) -> Vec<[Vec3<f32>; 3]> {
+	let read_vec3 = |record: &[u8], offset: usize| {
+		let read_f32 = |offset: usize| f32::from_le_bytes([record[offset], record[offset + 1], record[offset + 2], record[offset + 3]]);
+		Vec3(read_f32(offset), read_f32(offset + 4), read_f32(offset + 8))
+	};
+	// Each record is a normal, three corners and an attribute byte count
+	data[84..].chunks_exact(50).map(|record| {
+		[read_vec3(record, 12), read_vec3(record, 24), read_vec3(record, 36)]
+	}).collect()
+}
+
+fn parse_ascii_stl(data: &[u8]) -> Result<Vec<[Vec3<f32>; 3]>, String> {
+	let text = std::str::from_utf8(data).map_err(|_| String::from("STL file is neither binary nor ASCII"))?;
+	if !text.trim_start().starts_with("solid") {
+		return Err(String::from("STL file is neither binary nor ASCII"));
+	}
+	let mut corners = Vec::new();
+	let mut words = text.split_whitespace();
+	while let Some(word) = words.next() {
+		if word != "vertex" {
+			continue;
+		}
+		let mut coord = || -> Result<f32, String> {
+			let word = words.next().ok_or_else(|| String::from("Unexpected end of STL file"))?;
+			word.parse().map_err(|_| format!("Invalid STL coordinate: {}", word))
+		};
+		corners.push(Vec3(coord()?, coord()?, coord()?));
+	}
+	if corners.len() % 3 != 0 {
+		return Err(String::from("STL file has an incomplete triangle"));
+	}
+	Ok(corners.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect())
+}
+
+/// Mesh of an entity kind with its configuration.
+#[derive(Clone, Debug)]
+pub struct EntityMesh {
+	pub def: MeshDef,
+	pub model: MeshModel,
+}
+
+impl EntityMesh {
+	/// Loads the meshes configured in the data file from the data directory.
+	pub fn load_all(data_dir: &str, json: &str) -> Result<Vec<EntityMesh>, String> {
+		let mut meshes = Vec::new();
+		for def in MeshDef::parse_all(json)? {
+			let path = format!("{}/{}", data_dir, def.mesh);
+			let stl = std::fs::read(&path).map_err(|err| format!("{}: {}", path, err))?;
+			let model = MeshModel::parse_stl(&stl, def.scale).map_err(|err| format!("{}: {}", path, err))?;
+			meshes.push(EntityMesh { def, model });
+		}
+		Ok(meshes)
+	}
+}
@@ -59,7 +59,6 @@ impl Object {
 				}
 				if t >= 0.75 && self.unalive_after_anim {
 					self.live = false;
-					println!("Object {:?} reached destination", self.handle);
 				}
 				return;
 			},
@@ -146,157 +146,198 @@ impl ModelData {
 
 const TILE_SIZE: f32 = 32.0;
 
-fn draw_floor(cv: &mut shade::d2::Canvas<Vertex, Uniform>, pos: Vec3<f32>, sprite: Sprite, z1: f32, z2: f32, alpha: f32, t: Transform3<f32>) {
-	let gfx = sprite.index();
+/// Receives the triangles emitted by the draw functions.
+pub trait PrimSink {
+	/// Adds triangles with indices relative to the given vertices.
+	fn add_prim(&mut self, indices: &[u32], vertices: &[Vertex]);
+}
 
-	let mut p = cv.begin(shade::PrimType::Triangles, 4, 2);
-	p.add_indices_quad();
+impl PrimSink for shade::d2::Canvas<Vertex, Uniform> {
+	fn add_prim(&mut self, indices: &[u32], vertices: &[Vertex]) {
+		let mut p = self.begin(shade::PrimType::Triangles, vertices.len(), indices.len() / 3);
+		p.add_indices(indices);
+		for &vertex in vertices {
+			p.add_vertex(vertex);
+		}
+	}
+}
 
-	let x = pos.x;
-	let y = pos.y;
+/// Triangles built once and copied to the canvas every frame.
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+	pub vertices: Vec<Vertex>,
+	pub indices: Vec<u32>,
+}
+
+impl PrimSink for Mesh {
+	fn add_prim(&mut self, indices: &[u32], vertices: &[Vertex]) {
+		let base = self.vertices.len() as u32;
+		self.indices.extend(indices.iter().map(|&index| base + index));
+		self.vertices.extend_from_slice(vertices);
+	}
+}
+
+impl Mesh {
+	pub fn clear(&mut self) {
+		self.vertices.clear();
+		self.indices.clear();
+	}
+	pub fn draw(&self, cv: &mut impl PrimSink) {
+		if !self.indices.is_empty() {
+			cv.add_prim(&self.indices, &self.vertices);
+		}
+	}
+}
+
+pub const QUAD_INDICES: [u32; 6] = [0, 1, 2, 0, 2, 3];
+
+/// Splits the tile at the wrapped texture offset into spans of (tile start, tile end, texture start) fractions.
+fn scroll_spans(offset: f32) -> ([(f32, f32, f32); 2], usize) {
+	if offset <= 0.0 || offset >= 1.0 {
+		([(0.0, 1.0, 0.0), (0.0, 0.0, 0.0)], 1)
+	}
+	else {
+		([(0.0, 1.0 - offset, offset), (1.0 - offset, 1.0, 0.0)], 2)
+	}
+}
+
+fn draw_floor(cv: &mut impl PrimSink, pos: Vec3<f32>, gfx: &SpriteFrame, z1: f32, z2: f32, alpha: f32, t: Transform3<f32>) {
+	let x = pos.x - gfx.offset.x;
+	let y = pos.y - gfx.offset.y;
 	let z1 = z1 + pos.z;
 	let z2 = z2 + pos.z;
-	let a = (alpha * 255.0) as u8;
+	let color = [255, 255, 255, (alpha * 255.0) as u8];
 
 	let cx = x + TILE_SIZE * 0.5;
 	let cy = y + TILE_SIZE * 0.5;
 	let t = Transform3::translate((cx, cy, 0.0)) * t * Transform3::translate((-cx, -cy, 0.0));
 
-	let u = gfx.x as f32 * (TILE_SIZE + 2.0) + 1.0;
-	let v = gfx.y as f32 * (TILE_SIZE + 2.0) + 1.0;
-
-	p.add_vertex(Vertex {
-		pos: t * Vec3(x, y, z2),
-		uv: Vec2(u, v),
-		color: [255, 255, 255, a],
-	});
-	p.add_vertex(Vertex {
-		pos: t * Vec3(x, y + TILE_SIZE, z1),
-		uv: Vec2(u, v + TILE_SIZE),
-		color: [255, 255, 255, a],
-	});
-	p.add_vertex(Vertex {
-		pos: t * Vec3(x + TILE_SIZE, y + TILE_SIZE, z1),
-		uv: Vec2(u + TILE_SIZE, v + TILE_SIZE),
-		color: [255, 255, 255, a],
-	});
-	p.add_vertex(Vertex {
-		pos: t * Vec3(x + TILE_SIZE, y, z2),
-		uv: Vec2(u + TILE_SIZE, v),
-		color: [255, 255, 255, a],
-	});
+	let u = gfx.uv.x;
+	let v = gfx.uv.y;
+	let us = gfx.uv_size.x;
+	let vs = gfx.uv_size.y;
+
+	// The top edge is raised to z2 and the bottom edge to z1
+	let corner = |fx: f32, fy: f32| t * Vec3(x + fx * TILE_SIZE, y + fy * TILE_SIZE, z2 + (z1 - z2) * fy);
+	let uv = |fu: f32, fv: f32| Vec2(u + fu * us, v + fv * vs);
+
+	// Scrolling textures wrap around within the tile, each span is drawn as its own quad
+	let (xs, nx) = scroll_spans(gfx.scroll.x);
+	let (ys, ny) = scroll_spans(gfx.scroll.y);
+	for &(y0, y1, tv) in &ys[..ny] {
+		for &(x0, x1, tu) in &xs[..nx] {
+			let (tu1, tv1) = (tu + x1 - x0, tv + y1 - y0);
+			cv.add_prim(&QUAD_INDICES, &[
+				Vertex { pos: corner(x0, y0), uv: uv(tu, tv), color },
+				Vertex { pos: corner(x0, y1), uv: uv(tu, tv1), color },
+				Vertex { pos: corner(x1, y1), uv: uv(tu1, tv1), color },
+				Vertex { pos: corner(x1, y0), uv: uv(tu1, tv), color },
+			]);
+		}
+	}
 }
 
-fn draw_shadow(cv: &mut shade::d2::Canvas<Vertex, Uniform>, pos: Vec3<f32>, sprite: Sprite, skew: f32, a: f32) {
-	let gfx = sprite.index();
-
-	let mut p = cv.begin(shade::PrimType::Triangles, 4, 2);
-	p.add_indices_quad();
-
-	let x = pos.x;
-	let y = pos.y;
+fn draw_shadow(cv: &mut impl PrimSink, pos: Vec3<f32>, gfx: &SpriteFrame, skew: f32, a: f32) {
+	let x = pos.x - gfx.offset.x;
+	let y = pos.y - gfx.offset.y;
 	let s = skew;
 
-	let u = gfx.x as f32 * (TILE_SIZE + 2.0) + 1.0;
-	let v = gfx.y as f32 * (TILE_SIZE + 2.0) + 1.0;
+	let u = gfx.uv.x;
+	let v = gfx.uv.y;
+	let us = gfx.uv_size.x;
+	let vs = gfx.uv_size.y;
 	let a = (a * 128.0) as u8;
 
-	p.add_vertex(Vertex {
-		pos: Vec3(x + s, y, 0.5),
-		uv: Vec2(u, v),
-		color: [0, 0, 0, a],
-	});
-	p.add_vertex(Vertex {
-		pos: Vec3(x, y + TILE_SIZE, 0.5),
-		uv: Vec2(u, v + TILE_SIZE),
-		color: [0, 0, 0, a],
-	});
-	p.add_vertex(Vertex {
-		pos: Vec3(x + TILE_SIZE, y + TILE_SIZE, 0.5),
-		uv: Vec2(u + TILE_SIZE, v + TILE_SIZE),
-		color: [0, 0, 0, a],
-	});
-	p.add_vertex(Vertex {
-		pos: Vec3(x + s + TILE_SIZE, y, 0.5),
-		uv: Vec2(u + TILE_SIZE, v),
-		color: [0, 0, 0, a],
-	});
-}
-
-fn draw_wall(cv: &mut shade::d2::Canvas<Vertex, Uniform>, pos: Vec3<f32>, w: f32, sprite: Sprite, alpha: f32) {
-	let gfx = sprite.index();
-
-	let mut p = cv.begin(shade::PrimType::Triangles, 8, 10);
-
-	p.add_indices(&[
-		0, 1, 4, 4, 1, 5,
-		1, 2, 5, 5, 2, 6,
-		2, 3, 6, 6, 3, 7,
-		3, 0, 7, 7, 0, 4,
-		4, 6, 7, 4, 5, 6,
+	cv.add_prim(&QUAD_INDICES, &[
+		Vertex {
+			pos: Vec3(x + s, y, 0.5),
+			uv: Vec2(u, v),
+			color: [0, 0, 0, a],
+		},
+		Vertex {
+			pos: Vec3(x, y + TILE_SIZE, 0.5),
+			uv: Vec2(u, v + vs),
+			color: [0, 0, 0, a],
+		},
+		Vertex {
+			pos: Vec3(x + TILE_SIZE, y + TILE_SIZE, 0.5),
+			uv: Vec2(u + us, v + vs),
+			color: [0, 0, 0, a],
+		},
+		Vertex {
+			pos: Vec3(x + s + TILE_SIZE, y, 0.5),
+			uv: Vec2(u + us, v),
+			color: [0, 0, 0, a],
+		},
 	]);
+}
 
+fn draw_wall(cv: &mut impl PrimSink, pos: Vec3<f32>, w: f32, gfx: &SpriteFrame, alpha: f32) {
 	let x = pos.x;
 	let y = pos.y;
 	let z = pos.z;
 	let a = (alpha * 255.0) as u8;
 
-	let u = gfx.x as f32 * (TILE_SIZE + 2.0) + 1.0;
-	let v = gfx.y as f32 * (TILE_SIZE + 2.0) + 1.0;
+	let u = gfx.uv.x;
+	let v = gfx.uv.y;
+	let us = gfx.uv_size.x;
+	let vs = gfx.uv_size.y;
 
 	let s = 4.0 + w;//if matches!(sprite, Sprite::Wall) { 0.0 } else { 4.0 };
-	let t = 4.0;
+	let t = 4.0 * us / TILE_SIZE;
 	let h = 20.0; //if block.is_door() { 15.0 } else { 20.0 };
 
-	p.add_vertex(Vertex {
-		pos: Vec3(x + w, y + w, z),
-		uv: Vec2(u, v),
-		color: [255, 255, 255, a],
-	});
-	p.add_vertex(Vertex {
-		pos: Vec3(x + w, y + TILE_SIZE - w, z),
-		uv: Vec2(u, v + TILE_SIZE),
-		color: [255, 255, 255, a],
-	});
-	p.add_vertex(Vertex {
-		pos: Vec3(x + TILE_SIZE - w, y + TILE_SIZE - w, z),
-		uv: Vec2(u + TILE_SIZE, v + TILE_SIZE),
-		color: [255, 255, 255, a],
-	});
-	p.add_vertex(Vertex {
-		pos: Vec3(x + TILE_SIZE - w, y + w, z),
-		uv: Vec2(u + TILE_SIZE, v),
-		color: [255, 255, 255, a],
-	});
-
-	p.add_vertex(Vertex {
-		pos: Vec3(x + s, y + s, z + h),
-		uv: Vec2(u + t, v + t),
-		color: [255, 255, 255, a],
-	});
-	p.add_vertex(Vertex {
-		pos: Vec3(x + s, y + TILE_SIZE - s, z + h),
-		uv: Vec2(u + t, v + TILE_SIZE - t),
-		color: [255, 255, 255, a],
-	});
-	p.add_vertex(Vertex {
-		pos: Vec3(x + TILE_SIZE - s, y + TILE_SIZE - s, z + h),
-		uv: Vec2(u + TILE_SIZE - t, v + TILE_SIZE - t),
-		color: [255, 255, 255, a],
-	});
-	p.add_vertex(Vertex {
-		pos: Vec3(x + TILE_SIZE - s, y + s, z + h),
-		uv: Vec2(u + TILE_SIZE - t, v + t),
-		color: [255, 255, 255, a],
-	});
+	cv.add_prim(&[
+		0, 1, 4, 4, 1, 5,
+		1, 2, 5, 5, 2, 6,
+		2, 3, 6, 6, 3, 7,
+		3, 0, 7, 7, 0, 4,
+		4, 6, 7, 4, 5, 6,
+	], &[
+		Vertex {
+			pos: Vec3(x + w, y + w, z),
+			uv: Vec2(u, v),
+			color: [255, 255, 255, a],
+		},
+		Vertex {
+			pos: Vec3(x + w, y + TILE_SIZE - w, z),
+			uv: Vec2(u, v + vs),
+			color: [255, 255, 255, a],
+		},
+		Vertex {
+			pos: Vec3(x + TILE_SIZE - w, y + TILE_SIZE - w, z),
+			uv: Vec2(u + us, v + vs),
+			color: [255, 255, 255, a],
+		},
+		Vertex {
+			pos: Vec3(x + TILE_SIZE - w, y + w, z),
+			uv: Vec2(u + us, v),
+			color: [255, 255, 255, a],
+		},
+		Vertex {
+			pos: Vec3(x + s, y + s, z + h),
+			uv: Vec2(u + t, v + t),
+			color: [255, 255, 255, a],
+		},
+		Vertex {
+			pos: Vec3(x + s, y + TILE_SIZE - s, z + h),
+			uv: Vec2(u + t, v + vs - t),
+			color: [255, 255, 255, a],
+		},
+		Vertex {
+			pos: Vec3(x + TILE_SIZE - s, y + TILE_SIZE - s, z + h),
+			uv: Vec2(u + us - t, v + vs - t),
+			color: [255, 255, 255, a],
+		},
+		Vertex {
+			pos: Vec3(x + TILE_SIZE - s, y + s, z + h),
+			uv: Vec2(u + us - t, v + t),
+			color: [255, 255, 255, a],
+		},
+	]);
 }
 
-fn draw_portal(cv: &mut shade::d2::Canvas<Vertex, Uniform>, pos: Vec3<f32>, sprite: Sprite) {
-	let gfx = sprite.index();
-
-	let mut p = cv.begin(shade::PrimType::Triangles, 5, 4);
-	p.add_indices(&[0, 1, 2, 0, 2, 3, 0, 3, 4, 0, 4, 1]);
-
+fn draw_portal(cv: &mut impl PrimSink, pos: Vec3<f32>, gfx: &SpriteFrame) {
 	let x = pos.x;
 	let y = pos.y;
 	let z = pos.z;
@@ -304,101 +345,178 @@ fn draw_portal(cv: &mut shade::d2::Canvas<Vertex, Uniform>, pos: Vec3<f32>, spri
 	let cx = x + TILE_SIZE * 0.5;
 	let cy = y + TILE_SIZE * 0.5;
 
-	let u = gfx.x as f32 * (TILE_SIZE + 2.0) + 1.0;
-	let v = gfx.y as f32 * (TILE_SIZE + 2.0) + 1.0;
-	let cu = u + TILE_SIZE * 0.5;
-	let cv = v + TILE_SIZE * 0.5;
-
-	p.add_vertex(Vertex {
-		pos: Vec3(cx, cy, z - 10.0),
-		uv: Vec2(cu, cv),
-		color: [255, 255, 255, 255],
-	});
-	p.add_vertex(Vertex {
-		pos: Vec3(x, y, z),
-		uv: Vec2(u, v),
-		color: [255, 255, 255, 255],
-	});
-	p.add_vertex(Vertex {
-		pos: Vec3(x, y + TILE_SIZE, z),
-		uv: Vec2(u, v + TILE_SIZE),
-		color: [255, 255, 255, 255],
-	});
-	p.add_vertex(Vertex {
-		pos: Vec3(x + TILE_SIZE, y + TILE_SIZE, z),
-		uv: Vec2(u + TILE_SIZE, v + TILE_SIZE),
-		color: [255, 255, 255, 255],
-	});
-	p.add_vertex(Vertex {
-		pos: Vec3(x + TILE_SIZE, y, z),
-		uv: Vec2(u + TILE_SIZE, v),
-		color: [255, 255, 255, 255],
-	});
+	let u = gfx.uv.x;
+	let v = gfx.uv.y;
+	let us = gfx.uv_size.x;
+	let vs = gfx.uv_size.y;
+	let center_u = u + us * 0.5;
+	let center_v = v + vs * 0.5;
+
+	cv.add_prim(&[0, 1, 2, 0, 2, 3, 0, 3, 4, 0, 4, 1], &[
+		Vertex {
+			pos: Vec3(cx, cy, z - 10.0),
+			uv: Vec2(center_u, center_v),
+			color: [255, 255, 255, 255],
+		},
+		Vertex {
+			pos: Vec3(x, y, z),
+			uv: Vec2(u, v),
+			color: [255, 255, 255, 255],
+		},
+		Vertex {
+			pos: Vec3(x, y + TILE_SIZE, z),
+			uv: Vec2(u, v + vs),
+			color: [255, 255, 255, 255],
+		},
+		Vertex {
+			pos: Vec3(x + TILE_SIZE, y + TILE_SIZE, z),
+			uv: Vec2(u + us, v + vs),
+			color: [255, 255, 255, 255],
+		},
+		Vertex {
+			pos: Vec3(x + TILE_SIZE, y, z),
+			uv: Vec2(u + us, v),
+			color: [255, 255, 255, 255],
+		},
+	]);
 }
 
-fn draw(cv: &mut shade::d2::Canvas<Vertex, Uniform>, pos: Vec3<f32>, sprite: Sprite, model: Model, alpha: f32, t: Transform3<f32>) {
+fn draw(cv: &mut impl PrimSink, gfx: &SpriteFrame, pos: Vec3<f32>, model: Model, alpha: f32, t: Transform3<f32>) {
 	match model {
 		Model::Empty => (),
-		Model::Floor => draw_floor(cv, pos, sprite, 0.0, 0.0, alpha, t),
-		Model::Wall => draw_wall(cv, pos, 0.0, sprite, alpha),
-		Model::ThinWall => draw_wall(cv, pos, 2.0, sprite, alpha),
-		Model::Sprite => draw_floor(cv, pos, sprite, 0.0, 20.0, alpha, t),
-		Model::Portal => draw_portal(cv, pos, sprite),
-		Model::FlatSprite => draw_floor(cv, pos, sprite, 3.0, 12.0, alpha, t),
-		Model::ReallyFlatSprite => draw_floor(cv, pos, sprite, 6.0, 10.0, alpha, t),
-		Model::FloorSprite => draw_floor(cv, pos, sprite, 1.0, 1.0, alpha, t),
-		_ => unimplemented!(),
+		Model::Floor => draw_floor(cv, pos, gfx, 0.0, 0.0, alpha, t),
+		Model::Wall => draw_wall(cv, pos, 0.0, gfx, alpha),
+		Model::ThinWall => draw_wall(cv, pos, 2.0, gfx, alpha),
+		Model::Sprite => draw_floor(cv, pos, gfx, 0.0, 20.0, alpha, t),
+		Model::Portal => draw_portal(cv, pos, gfx),
+		Model::FlatSprite => draw_floor(cv, pos, gfx, 3.0, 12.0, alpha, t),
+		Model::ReallyFlatSprite => draw_floor(cv, pos, gfx, 6.0, 10.0, alpha, t),
+		Model::FloorSprite => draw_floor(cv, pos, gfx, 1.0, 1.0, alpha, t),
+		Model::WallV2 | Model::SpriteShadow => unimplemented!(),
 	}
 }
 
-pub fn draw_tile(cv: &mut shade::d2::Canvas::<render::Vertex, render::Uniform>, terrain: core::Terrain, pos: Vec3<f32>, tiles: &[TileGfx]) {
+pub fn draw_tile(cv: &mut impl PrimSink, atlas: &SpriteAtlas, terrain: core::Terrain, pos: Vec3<f32>, tiles: &[TileGfx]) {
 	let tile = tiles[terrain as usize];
-	draw(cv, pos, tile.sprite, tile.model, 1.0, Transform3::IDENTITY);
+	draw(cv, &atlas.frame(tile.sprite, 0.0), pos, tile.model, 1.0, Transform3::IDENTITY);
 }
 
-pub fn field(cv: &mut shade::d2::Canvas::<render::Vertex, render::Uniform>, state: &VisualState, time: f32) {
-	let i = (time * 8.0) as i32;
-	let field = &state.game.field;
-	let resx = &state.resources;
+/// Draws the sprite flat on the floor.
+pub fn draw_sprite(cv: &mut impl PrimSink, atlas: &SpriteAtlas, sprite: Sprite, pos: Vec3<f32>) {
+	draw(cv, &atlas.frame(sprite, 0.0), pos, Model::Floor, 1.0, Transform3::IDENTITY);
+}
+
+/// Draws the terrain tile at the given time.
+pub fn draw_terrain(cv: &mut impl PrimSink, atlas: &SpriteAtlas, time: f32, pos: Vec2<i32>, tile: TileGfx) {
+	draw(cv, &atlas.frame(tile.sprite, time), pos.map(|c| c as f32 * 32.0).vec3(0.0), tile.model, 1.0, Transform3::IDENTITY);
+}
+
+/// Returns if the object's tile sized box is inside the view frustum.
+fn is_visible(frustum: &Frustum, pos: Vec3<f32>) -> bool {
+	frustum.contains_box(pos, pos + Vec3(32.0, 32.0, 32.0))
+}
+
+pub fn field(cv: &mut impl DrawBackend, state: &VisualState, time: f32) {
+	let atlas = &state.resources.atlas;
+	let frustum = state.camera.frustum();
 	// Render the level geometry
-	cv.blend_mode = shade::BlendMode::Solid;
-	for y in 0..field.height {
-		for x in 0..field.width {
-			let tile = field.get_terrain(Vec2(x, y));
-			let tile = state.tiles[tile as usize];
-			if tile.sprite == Sprite::Blank || tile.model == Model::Empty {
-				continue;
-			}
-			let (mut sprite, model) = (tile.sprite, tile.model);
-			if tile.sprite == Sprite::Exit1 {
-				match i % 3 {
-					2 => sprite = Sprite::Exit1,
-					1 => sprite = Sprite::Exit2,
-					0 => sprite = Sprite::Exit3,
-					_ => (),
-				}
-			}
-			draw(cv, Vec3(x, y, 0).map(|c| c as f32 * 32.0), sprite, model, 1.0, Transform3::IDENTITY);
-		}
-	}
+	cv.set_blend(Blend::Solid);
+	state.terrain_cache.draw(cv, &frustum, atlas, time);
+	// Objects are drawn in the order they were created so overlapping translucent objects do not flicker
+	let mut objects: Vec<&Object> = state.objects.map.values().collect();
+	objects.sort_by_key(|obj| obj.handle.0);
 	// Render the object shadows
-	cv.blend_mode = shade::BlendMode::Alpha;
-	for obj in state.objects.map.values() {
-		if !obj.live || !obj.vis {
+	cv.set_blend(Blend::Alpha);
+	for &obj in &objects {
+		if !obj.live || !obj.vis || !is_visible(&frustum, obj.pos) {
+			continue;
+		}
+		if state.resources.mesh(obj.entity_kind).is_some() {
 			continue;
 		}
 		if matches!(obj.model, Model::Sprite | Model::FlatSprite) {
-			draw_shadow(cv, obj.pos, obj.sprite, 10.0, obj.alpha);
+			draw_shadow(cv, obj.pos, &atlas.frame(obj.sprite, time), 10.0, obj.alpha);
 		}
 		if matches!(obj.model, Model::ReallyFlatSprite) {
-			draw_shadow(cv, obj.pos, obj.sprite, 2.0, obj.alpha);
+			draw_shadow(cv, obj.pos, &atlas.frame(obj.sprite, time), 2.0, obj.alpha);
 		}
 	}
 	// Render the objects
-	for obj in state.objects.map.values() {
-		if !obj.live || !obj.vis {
+	for &obj in &objects {
+		if !obj.live || !obj.vis || !is_visible(&frustum, obj.pos) {
+			continue;
+		}
+		if let Some(mesh) = state.resources.mesh(obj.entity_kind) {
+			let face_dir = state.game.ents.get(obj.entity_handle).and_then(|ent| ent.face_dir);
+			draw_mesh(cv, atlas, time, obj, mesh, face_dir);
 			continue;
 		}
-		draw(cv, obj.pos, obj.sprite, obj.model, obj.alpha, Transform3::IDENTITY);
+		draw(cv, &atlas.frame(obj.sprite, time), obj.pos, obj.model, obj.alpha, Transform3::IDENTITY);
 	}
+	// Render the effects
+	for particle in &state.effects.particles {
+		let extent = Vec3(particle.size, particle.size, particle.size);
+		if !frustum.contains_box(particle.pos - extent, particle.pos + extent) {
+			continue;
+		}
+		draw_particle(cv, &atlas.frame(particle.sprite, time), particle);
+	}
+}
+
+/// Direction towards the light used to shade meshes.
+const LIGHT_DIR: Vec3<f32> = Vec3 { x: -0.303, y: -0.505, z: 0.808 };
+
+/// Draws the object's mesh standing on its tile, turned to the facing direction if configured.
+fn draw_mesh(cv: &mut impl PrimSink, atlas: &SpriteAtlas, time: f32, obj: &Object, mesh: &EntityMesh, face_dir: Option<core::Dir>) {
+	let gfx = &atlas.frame(mesh.def.sprite.unwrap_or(obj.sprite), time);
+	let angle = match face_dir {
+		Some(dir) if mesh.def.rotate => match dir {
+			core::Dir::Up => 0.0,
+			core::Dir::Right => 0.5,
+			core::Dir::Down => 1.0,
+			core::Dir::Left => 1.5,
+		},
+		_ => 0.0,
+	} * std::f32::consts::PI;
+	let (sin, cos) = f32::sin_cos(angle);
+	let rotate = |v: Vec3<f32>| Vec3(v.x * cos - v.y * sin, v.x * sin + v.y * cos, v.z);
+	let center = obj.pos + Vec3(16.0, 16.0, 0.0);
+	let [r, g, b, a] = mesh.def.color;
+	let alpha = (a as f32 * obj.alpha) as u8;
+
+	let model = &mesh.model;
+	let vertices = (0..model.positions.len()).map(|i| {
+		// Bake simple diffuse lighting into the vertex color
+		let normal = rotate(model.normals[i]);
+		let diffuse = f32::max(0.0, normal.x * LIGHT_DIR.x + normal.y * LIGHT_DIR.y + normal.z * LIGHT_DIR.z);
+		let light = 0.4 + 0.6 * diffuse;
+		let shade = |c: u8| (c as f32 * light) as u8;
+		Vertex {
+			pos: center + rotate(model.positions[i]),
+			uv: gfx.uv + model.uvs[i] * gfx.uv_size,
+			color: [shade(r), shade(g), shade(b), alpha],
+		}
+	}).collect::<Vec<_>>();
+	cv.add_prim(&model.indices, &vertices);
+}
+
+/// Draws the particle as a small quad tilted towards the camera, textured with the center of the sprite.
+fn draw_particle(cv: &mut impl PrimSink, gfx: &SpriteFrame, particle: &Particle) {
+	let s = particle.size * 0.5;
+	let Vec3 { x, y, z } = particle.pos;
+
+	let u = gfx.uv.x + gfx.uv_size.x * 0.375;
+	let v = gfx.uv.y + gfx.uv_size.y * 0.375;
+	let us = gfx.uv_size.x * 0.25;
+	let vs = gfx.uv_size.y * 0.25;
+
+	let [r, g, b, a] = particle.color;
+	let color = [r, g, b, (a as f32 * particle.alpha()) as u8];
+
+	cv.add_prim(&QUAD_INDICES, &[
+		Vertex { pos: Vec3(x - s, y - s, z + s * 2.0), uv: Vec2(u, v), color },
+		Vertex { pos: Vec3(x - s, y + s, z), uv: Vec2(u, v + vs), color },
+		Vertex { pos: Vec3(x + s, y + s, z), uv: Vec2(u + us, v + vs), color },
+		Vertex { pos: Vec3(x + s, y - s, z + s * 2.0), uv: Vec2(u + us, v), color },
+	]);
 }
@@ -4,6 +4,126 @@ use super::*;
 pub struct Resources {
 	pub tileset: shade::Texture2D,
 	pub tileset_size: Vec2<i32>,
+	pub atlas: SpriteAtlas,
+	pub font: shade::Texture2D,
+	pub font_size: Vec2<i32>,
+	pub font_atlas: FontAtlas,
+	/// Meshes drawn in place of sprites indexed by entity kind.
+	pub meshes: Vec<Option<EntityMesh>>,
 	pub shader: shade::Shader,
 	pub screen_size: Vec2<i32>,
+	/// Source files watched for hot reloading.
+	pub watch: Option<ResourceFiles>,
+	/// Skins to switch between, hot reloads update the current skin.
+	pub skins: Vec<Skin>,
+	/// Index of the current skin in the skins.
+	pub skin_index: usize,
+}
+
+/// Tileset texture with its sprite atlas.
+#[derive(Clone)]
+pub struct Skin {
+	pub atlas: SpriteAtlas,
+	pub tileset: shade::Texture2D,
+	pub tileset_size: Vec2<i32>,
+}
+
+impl Skin {
+	pub fn new(atlas: SpriteAtlas, tileset: shade::Texture2D, tileset_size: Vec2<i32>) -> Result<Skin, String> {
+		atlas.validate(tileset_size)?;
+		Ok(Skin { atlas, tileset, tileset_size })
+	}
+	/// Loads the tileset texture of the atlas from the file, adding the atlas gutter around its tiles.
+	pub fn load(g: &mut shade::Graphics, atlas: SpriteAtlas, path: &str) -> Result<Skin, String> {
+		let mut gutter = shade::png::gutter(atlas.tile_size, atlas.tile_size, atlas.gutter);
+		let tileset = shade::png::load(g, Some(atlas.name.as_str()), path, &shade::png::TextureProps {
+			filter_min: shade::TextureFilter::Linear,
+			filter_mag: shade::TextureFilter::Linear,
+			wrap_u: shade::TextureWrap::ClampEdge,
+			wrap_v: shade::TextureWrap::ClampEdge,
+		}, Some(&mut gutter)).map_err(|err| format!("Failed to load {}: {:?}", path, err))?;
+		let tex_info = g.texture2d_get_info(tileset).map_err(|err| format!("Failed to load {}: {:?}", path, err))?;
+		Skin::new(atlas, tileset, [tex_info.width, tex_info.height].into())
+	}
+}
+
+/// Font texture with its glyph metrics.
+#[derive(Clone)]
+pub struct Font {
+	pub atlas: FontAtlas,
+	pub texture: shade::Texture2D,
+	pub texture_size: Vec2<i32>,
+}
+
+impl Font {
+	pub fn new(atlas: FontAtlas, texture: shade::Texture2D, texture_size: Vec2<i32>) -> Result<Font, String> {
+		atlas.validate(texture_size)?;
+		Ok(Font { atlas, texture, texture_size })
+	}
+}
+
+impl Resources {
+	pub fn set_skin(&mut self, skin: &Skin) {
+		self.tileset = skin.tileset;
+		self.tileset_size = skin.tileset_size;
+		self.atlas = skin.atlas.clone();
+	}
+	/// Adds the skin to the skins to switch between, the first skin added is used.
+	pub fn add_skin(&mut self, skin: Skin) {
+		if self.skins.is_empty() {
+			self.skin_index = 0;
+			self.set_skin(&skin);
+		}
+		self.skins.push(skin);
+	}
+	/// Switches to the next skin.
+	pub fn next_skin(&mut self) {
+		if self.skins.is_empty() {
+			return;
+		}
+		self.skin_index = (self.skin_index + 1) % self.skins.len();
+		let skin = self.skins[self.skin_index].clone();
+		self.set_skin(&skin);
+	}
+	pub fn set_font(&mut self, font: &Font) {
+		self.font = font.texture;
+		self.font_size = font.texture_size;
+		self.font_atlas = font.atlas.clone();
+	}
+	/// Compiles the shader and uses it in place of the current shader, the current shader is kept if compiling fails.
+	///
+	/// The replaced shader is released.
+	pub fn load_shader(&mut self, g: &mut shade::Graphics, vs: &str, fs: &str) -> Result<(), String> {
+		let shader = g.shader_create(None).map_err(|err| format!("Failed to create shader: {:?}", err))?;
+		if g.shader_compile(shader, vs, fs).is_err() {
+			let log = g.shader_compile_log(shader).unwrap_or_default();
+			let _ = g.shader_free(shader, shade::FreeMode::Delete);
+			return Err(format!("Failed to compile shader: {}", log));
+		}
+		let old = std::mem::replace(&mut self.shader, shader);
+		if old != shade::Shader::default() {
+			let _ = g.shader_free(old, shade::FreeMode::Delete);
+		}
+		Ok(())
+	}
+	/// Draws entities of the mesh's kind with the mesh instead of their sprite.
+	pub fn set_mesh(&mut self, mesh: EntityMesh) {
+		let index = mesh.def.kind as usize;
+		if self.meshes.len() <= index {
+			self.meshes.resize(core::ENTITY_KIND_COUNT, None);
+		}
+		self.meshes[index] = Some(mesh);
+	}
+	#[inline]
+	pub fn mesh(&self, kind: core::EntityKind) -> Option<&EntityMesh> {
+		self.meshes.get(kind as usize)?.as_ref()
+	}
+	/// Returns the uniform for drawing sprites with the tileset texture.
+	pub fn tileset_uniform(&self, transform: Mat4<f32>) -> render::Uniform {
+		render::Uniform { transform, texture: self.tileset, texture_size: self.tileset_size.map(|c| c as f32).into() }
+	}
+	/// Returns the uniform for drawing text with the font texture.
+	pub fn font_uniform(&self, transform: Mat4<f32>) -> render::Uniform {
+		render::Uniform { transform, texture: self.font, texture_size: self.font_size.map(|c| c as f32).into() }
+	}
 }
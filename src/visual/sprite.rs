@@ -100,103 +100,319 @@ pub enum Sprite {
 	ParameciumLeftRight,
 }
 
-impl Sprite {
-	pub fn index(self) -> Vec2<i32> {
-		match self {
-			Sprite::Blank => Vec2(1, 5),
-			Sprite::Floor => Vec2(0, 0),
-			Sprite::Wall => Vec2(0, 1),
-			Sprite::Chip => Vec2(0, 2),
-			Sprite::Socket => Vec2(2, 2),
-			Sprite::Exit1 => Vec2(3, 9),
-			Sprite::Exit2 => Vec2(3, 10),
-			Sprite::Exit3 => Vec2(3, 11),
-			Sprite::Hint => Vec2(2, 15),
-			Sprite::Water => Vec2(0, 3),
-			Sprite::Block => Vec2(1, 0),
-			Sprite::Dirt => Vec2(0, 11),
-			Sprite::Gravel => Vec2(2, 13),
-			Sprite::Fire => Vec2(0, 4),
-			Sprite::Ice => Vec2(0, 12),
-			Sprite::IceUL => Vec2(1, 10),
-			Sprite::IceUR => Vec2(1, 11),
-			Sprite::IceDR => Vec2(1, 12),
-			Sprite::IceDL => Vec2(1, 13),
-			Sprite::ForceUp => Vec2(1, 2),
-			Sprite::ForceLeft => Vec2(1, 4),
-			Sprite::ForceDown => Vec2(0, 13),
-			Sprite::ForceRight => Vec2(1, 3),
-			Sprite::ForceRandom => Vec2(3, 2),
-			Sprite::CloneMachine => Vec2(3, 1),
-			Sprite::PanelNorth => Vec2(0, 6),
-			Sprite::PanelWest => Vec2(0, 7),
-			Sprite::PanelSouth => Vec2(0, 8),
-			Sprite::PanelEast => Vec2(0, 9),
-			Sprite::PanelSE => Vec2(3, 0),
-			Sprite::PowerFlippers => Vec2(6, 8),
-			Sprite::PowerFireBoots => Vec2(6, 9),
-			Sprite::PowerIceSkates => Vec2(6, 10),
-			Sprite::PowerSuctionBoots => Vec2(6, 11),
-			Sprite::PlayerCheer => Vec2(3, 8),
-			Sprite::PlayerWalkNeutral => Vec2(3, 4),
-			Sprite::PlayerWalkUp => Vec2(6, 12),
-			Sprite::PlayerWalkLeft => Vec2(6, 13),
-			Sprite::PlayerWalkDown => Vec2(6, 14),
-			Sprite::PlayerWalkRight => Vec2(6, 15),
-			Sprite::PlayerSwimNeutral => Vec2(2, 12),
-			Sprite::PlayerSwimUp => Vec2(3, 12),
-			Sprite::PlayerSwimLeft => Vec2(3, 13),
-			Sprite::PlayerSwimDown => Vec2(3, 14),
-			Sprite::PlayerSwimRight => Vec2(3, 15),
-			Sprite::PlayerBurned => Vec2(3, 5),
-			Sprite::PlayerDead => Vec2(3, 5),
-			Sprite::WaterSplash => Vec2(3, 3),
-			Sprite::BlueKey => Vec2(6, 4),
-			Sprite::RedKey => Vec2(6, 5),
-			Sprite::GreenKey => Vec2(6, 6),
-			Sprite::YellowKey => Vec2(6, 7),
-			Sprite::BlueLock => Vec2(1, 6),
-			Sprite::RedLock => Vec2(1, 7),
-			Sprite::GreenLock => Vec2(1, 8),
-			Sprite::YellowLock => Vec2(1, 9),
-			Sprite::HiddenWall => Vec2(1, 1),
-			Sprite::InvisWall => Vec2(2, 0),
-			Sprite::BlueWall => Vec2(1, 14),
-			Sprite::BlueWallFake => Vec2(1, 15),
-			Sprite::GreenSwitch => Vec2(2, 3),
-			Sprite::RedSwitch => Vec2(2, 4),
-			Sprite::BrownSwitch => Vec2(2, 7),
-			Sprite::BlueSwitch => Vec2(2, 8),
-			Sprite::OnOffWall => Vec2(2, 5),
-			Sprite::OnOffFloor => Vec2(2, 6),
-			Sprite::Bomb => Vec2(2, 10),
-			Sprite::Thief => Vec2(2, 1),
-			Sprite::Teleport => Vec2(2, 9),
-			Sprite::BearTrap => Vec2(2, 11),
-			Sprite::RecessedWall => Vec2(2, 14),
-			Sprite::BugUp => Vec2(4, 0),
-			Sprite::BugLeft => Vec2(4, 1),
-			Sprite::BugDown => Vec2(4, 2),
-			Sprite::BugRight => Vec2(4, 3),
-			Sprite::TankUp => Vec2(4, 12),
-			Sprite::TankLeft => Vec2(4, 13),
-			Sprite::TankDown => Vec2(4, 14),
-			Sprite::TankRight => Vec2(4, 15),
-			Sprite::PinkBall => Vec2(4, 5),
-			Sprite::FireBall => Vec2(4, 4),
-			Sprite::GliderUp => Vec2(5, 0),
-			Sprite::GliderLeft => Vec2(5, 1),
-			Sprite::GliderDown => Vec2(5, 2),
-			Sprite::GliderRight => Vec2(5, 3),
-			Sprite::TeethUp => Vec2(5, 4),
-			Sprite::TeethLeft => Vec2(5, 5),
-			Sprite::TeethDown => Vec2(5, 6),
-			Sprite::TeethRight => Vec2(5, 7),
-			Sprite::WalkerUpDown => Vec2(5, 8),
-			Sprite::WalkerLeftRight => Vec2(5, 9),
-			Sprite::Blob => Vec2(5, 12),
-			Sprite::ParameciumUpDown => Vec2(6, 0),
-			Sprite::ParameciumLeftRight => Vec2(6, 1),
+pub const SPRITE_COUNT: usize = 94;
+
+pub static ALL_SPRITES: [Sprite; SPRITE_COUNT] = [
+	Sprite::Blank,
+	Sprite::Floor,
+	Sprite::Wall,
+	Sprite::Chip,
+	Sprite::Socket,
+	Sprite::Exit1,
+	Sprite::Exit2,
+	Sprite::Exit3,
+	Sprite::Hint,
+	Sprite::Water,
+	Sprite::Block,
+	Sprite::Dirt,
+	Sprite::Gravel,
+	Sprite::Fire,
+	Sprite::Ice,
+	Sprite::IceUL,
+	Sprite::IceUR,
+	Sprite::IceDL,
+	Sprite::IceDR,
+	Sprite::ForceUp,
+	Sprite::ForceLeft,
+	Sprite::ForceDown,
+	Sprite::ForceRight,
+	Sprite::ForceRandom,
+	Sprite::CloneMachine,
+	Sprite::PanelNorth,
+	Sprite::PanelWest,
+	Sprite::PanelSouth,
+	Sprite::PanelEast,
+	Sprite::PanelSE,
+	Sprite::PowerFlippers,
+	Sprite::PowerFireBoots,
+	Sprite::PowerIceSkates,
+	Sprite::PowerSuctionBoots,
+	Sprite::PlayerCheer,
+	Sprite::PlayerWalkNeutral,
+	Sprite::PlayerWalkUp,
+	Sprite::PlayerWalkLeft,
+	Sprite::PlayerWalkDown,
+	Sprite::PlayerWalkRight,
+	Sprite::PlayerSwimNeutral,
+	Sprite::PlayerSwimUp,
+	Sprite::PlayerSwimLeft,
+	Sprite::PlayerSwimDown,
+	Sprite::PlayerSwimRight,
+	Sprite::PlayerBurned,
+	Sprite::PlayerDead,
+	Sprite::WaterSplash,
+	Sprite::BlueKey,
+	Sprite::RedKey,
+	Sprite::GreenKey,
+	Sprite::YellowKey,
+	Sprite::BlueLock,
+	Sprite::RedLock,
+	Sprite::GreenLock,
+	Sprite::YellowLock,
+	Sprite::HiddenWall,
+	Sprite::InvisWall,
+	Sprite::BlueWall,
+	Sprite::BlueWallFake,
+	Sprite::GreenSwitch,
+	Sprite::RedSwitch,
+	Sprite::BrownSwitch,
+	Sprite::BlueSwitch,
+	Sprite::OnOffWall,
+	Sprite::OnOffFloor,
+	Sprite::Bomb,
+	Sprite::Thief,
+	Sprite::Teleport,
+	Sprite::BearTrap,
+	Sprite::RecessedWall,
+	Sprite::BugUp,
+	Sprite::BugLeft,
+	Sprite::BugDown,
+	Sprite::BugRight,
+	Sprite::TankUp,
+	Sprite::TankLeft,
+	Sprite::TankDown,
+	Sprite::TankRight,
+	Sprite::PinkBall,
+	Sprite::FireBall,
+	Sprite::GliderUp,
+	Sprite::GliderLeft,
+	Sprite::GliderDown,
+	Sprite::GliderRight,
+	Sprite::WalkerUpDown,
+	Sprite::WalkerLeftRight,
+	Sprite::TeethUp,
+	Sprite::TeethLeft,
+	Sprite::TeethDown,
+	Sprite::TeethRight,
+	Sprite::Blob,
+	Sprite::ParameciumUpDown,
+	Sprite::ParameciumLeftRight,
+];
+
+/// Sprite definition in the tileset grid.
+///
+/// Animation frames are laid out in consecutive cells below the first frame.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SpriteDef {
+	pub index: Vec2<i32>,
+	pub frames: i32,
+	pub frame_time: f32,
+	/// Sprite origin in texels, the sprite is drawn offset by this amount.
+	pub origin: Vec2<f32>,
+}
+
+/// Animation of a sprite evaluated at draw time, in addition to the frames of its definition.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SpriteAnim {
+	/// Sprites shown in turn instead of the sprite itself.
+	pub sequence: Vec<Sprite>,
+	/// Time each sprite of the sequence is shown.
+	pub frame_time: f32,
+	/// Every other frame of the sequence is mirrored horizontally.
+	pub mirror: bool,
+	/// Velocity of the texture in tiles per second, it wraps around within the tile.
+	pub scroll: Vec2<f32>,
+}
+
+impl SpriteAnim {
+	pub fn is_animated(&self) -> bool {
+		!self.sequence.is_empty() || self.scroll != Vec2::ZERO
+	}
+}
+
+/// Resolved texture rect of a sprite animation frame.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SpriteFrame {
+	pub uv: Vec2<f32>,
+	pub uv_size: Vec2<f32>,
+	/// World space offset derived from the sprite origin.
+	pub offset: Vec2<f32>,
+	/// Texture offset as a fraction of the sprite in the range 0.0 to 1.0, the texture wraps around.
+	pub scroll: Vec2<f32>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum SpriteDto {
+	Index(Vec2<i32>),
+	Def {
+		index: Vec2<i32>,
+		#[serde(default = "default_frames")]
+		frames: i32,
+		#[serde(default)]
+		frame_time: f32,
+		#[serde(default)]
+		origin: Vec2<f32>,
+	},
+}
+
+fn default_frames() -> i32 {
+	1
+}
+
+#[derive(serde::Deserialize)]
+struct SpriteAnimDto {
+	#[serde(default)]
+	sequence: Vec<Sprite>,
+	#[serde(default)]
+	frame_time: f32,
+	#[serde(default)]
+	mirror: bool,
+	#[serde(default)]
+	scroll: Vec2<f32>,
+}
+
+#[derive(serde::Deserialize)]
+struct TilesetDto {
+	name: String,
+	texture: String,
+	tile_size: i32,
+	gutter: i32,
+	sprites: HashMap<Sprite, SpriteDto>,
+	#[serde(default)]
+	animations: HashMap<Sprite, SpriteAnimDto>,
+}
+
+#[derive(serde::Deserialize)]
+struct DataDto {
+	tilesets: Vec<TilesetDto>,
+}
+
+/// Sprite atlas of a tileset texture.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpriteAtlas {
+	pub name: String,
+	/// Texture file name relative to the data directory.
+	pub texture: String,
+	pub tile_size: i32,
+	/// Gutter in texels added around every tile when the texture is loaded.
+	pub gutter: i32,
+	pub sprites: Vec<SpriteDef>,
+	/// Animations indexed by sprite, most sprites are not animated.
+	pub animations: Vec<SpriteAnim>,
+}
+
+static BUILTIN_ATLAS: std::sync::OnceLock<SpriteAtlas> = std::sync::OnceLock::new();
+
+impl Default for SpriteAtlas {
+	fn default() -> Self {
+		BUILTIN_ATLAS.get_or_init(|| SpriteAtlas::parse_all(include_str!("../../data/data.json")).unwrap().swap_remove(0)).clone()
+	}
+}
+
+impl SpriteAtlas {
+	/// Parses the `tilesets` section of the data file.
+	pub fn parse_all(json: &str) -> Result<Vec<SpriteAtlas>, String> {
+		let dto: DataDto = serde_json::from_str(json).map_err(|err| err.to_string())?;
+		if dto.tilesets.is_empty() {
+			return Err(String::from("No tilesets defined"));
+		}
+		dto.tilesets.into_iter().map(SpriteAtlas::from_dto).collect()
+	}
+
+	fn from_dto(dto: TilesetDto) -> Result<SpriteAtlas, String> {
+		if dto.tile_size <= 0 || dto.gutter < 0 {
+			return Err(format!("Invalid tile size for tileset {}", dto.name));
+		}
+
+		let mut sprites = Vec::with_capacity(SPRITE_COUNT);
+		for &sprite in ALL_SPRITES.iter() {
+			let def = match dto.sprites.get(&sprite) {
+				Some(&SpriteDto::Index(index)) => SpriteDef { index, frames: 1, frame_time: 0.0, origin: Vec2::ZERO },
+				Some(&SpriteDto::Def { index, frames, frame_time, origin }) => SpriteDef { index, frames, frame_time, origin },
+				None => return Err(format!("Missing sprite {:?} in tileset {}", sprite, dto.name)),
+			};
+			if def.frames < 1 || def.frame_time < 0.0 || (def.frames > 1 && def.frame_time == 0.0) {
+				return Err(format!("Invalid animation for sprite {:?} in tileset {}", sprite, dto.name));
+			}
+			sprites.push(def);
+		}
+
+		let mut animations = vec![SpriteAnim::default(); SPRITE_COUNT];
+		for (sprite, anim) in dto.animations {
+			if anim.frame_time < 0.0 || (!anim.sequence.is_empty() && anim.frame_time == 0.0) {
+				return Err(format!("Invalid animation for sprite {:?} in tileset {}", sprite, dto.name));
+			}
+			animations[sprite as usize] = SpriteAnim {
+				sequence: anim.sequence,
+				frame_time: anim.frame_time,
+				mirror: anim.mirror,
+				scroll: anim.scroll,
+			};
+		}
+
+		Ok(SpriteAtlas {
+			name: dto.name,
+			texture: dto.texture,
+			tile_size: dto.tile_size,
+			gutter: dto.gutter,
+			sprites,
+			animations,
+		})
+	}
+
+	/// Checks that every sprite frame fits inside the tileset texture.
+	pub fn validate(&self, tileset_size: Vec2<i32>) -> Result<(), String> {
+		let stride = self.tile_size + self.gutter * 2;
+		for &sprite in ALL_SPRITES.iter() {
+			let def = self.get(sprite);
+			let min = def.index.map(|c| c * stride + self.gutter);
+			let max = Vec2(min.x + self.tile_size, min.y + (def.frames - 1) * stride + self.tile_size);
+			if min.x < 0 || min.y < 0 || max.x > tileset_size.x || max.y > tileset_size.y {
+				return Err(format!("Sprite {:?} is outside of tileset {} ({}x{})", sprite, self.name, tileset_size.x, tileset_size.y));
+			}
+		}
+		Ok(())
+	}
+
+	#[inline]
+	pub fn get(&self, sprite: Sprite) -> &SpriteDef {
+		&self.sprites[sprite as usize]
+	}
+
+	#[inline]
+	pub fn anim(&self, sprite: Sprite) -> &SpriteAnim {
+		&self.animations[sprite as usize]
+	}
+
+	/// Returns if the sprite changes over time.
+	pub fn is_animated(&self, sprite: Sprite) -> bool {
+		self.get(sprite).frames > 1 || self.anim(sprite).is_animated()
+	}
+
+	/// Returns the sprite animation frame at the given time.
+	pub fn frame(&self, sprite: Sprite, time: f32) -> SpriteFrame {
+		let anim = self.anim(sprite);
+		let (sprite, mirrored) = if anim.sequence.is_empty() { (sprite, false) } else {
+			let step = f32::max(0.0, time / anim.frame_time) as usize;
+			(anim.sequence[step % anim.sequence.len()], anim.mirror && step % 2 == 1)
+		};
+
+		let def = self.get(sprite);
+		let frame = if def.frames > 1 { f32::max(0.0, time / def.frame_time) as i32 % def.frames } else { 0 };
+		let stride = (self.tile_size + self.gutter * 2) as f32;
+		// Sprites are drawn on 32 world units per tile
+		let scale = 32.0 / self.tile_size as f32;
+		let mut gfx = SpriteFrame {
+			uv: Vec2(def.index.x as f32 * stride, (def.index.y + frame) as f32 * stride) + Vec2(self.gutter as f32, self.gutter as f32),
+			uv_size: Vec2(self.tile_size as f32, self.tile_size as f32),
+			offset: def.origin * scale,
+			scroll: (anim.scroll * -time).map(|c| c.rem_euclid(1.0)),
+		};
+		if mirrored {
+			gfx.uv.x += gfx.uv_size.x;
+			gfx.uv_size.x = -gfx.uv_size.x;
 		}
+		gfx
 	}
 }
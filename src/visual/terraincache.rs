@@ -0,0 +1,148 @@
+use super::*;
+
+/// Width and height of a chunk in tiles.
+const CHUNK_SIZE: i32 = 16;
+
+#[derive(Default)]
+struct Chunk {
+	/// Geometry of the static terrain.
+	mesh: Mesh,
+	/// Tiles with animated sprites, these are drawn every frame.
+	animated: Vec<(Vec2<i32>, TileGfx)>,
+	/// The terrain may have changed since the mesh was built.
+	dirty: bool,
+}
+
+/// Terrain geometry cached in chunks and rebuilt only where the terrain changes.
+#[derive(Default)]
+pub struct TerrainCache {
+	chunks: Vec<Chunk>,
+	/// Number of chunks in each direction.
+	size: Vec2<i32>,
+	/// Width of the map in tiles.
+	width: i32,
+	/// Terrain the meshes were built from.
+	terrain: Vec<core::Terrain>,
+	/// Tile visuals and sprite atlas the meshes were built with.
+	tiles: Vec<TileGfx>,
+	atlas: SpriteAtlas,
+}
+
+impl TerrainCache {
+	/// Marks the chunk containing the tile to be checked for changes.
+	pub fn invalidate(&mut self, pos: Vec2<i32>) {
+		if pos.x < 0 || pos.y < 0 {
+			return;
+		}
+		let chunk = pos.map(|c| c / CHUNK_SIZE);
+		if chunk.x < self.size.x && chunk.y < self.size.y {
+			self.chunks[(chunk.y * self.size.x + chunk.x) as usize].dirty = true;
+		}
+	}
+	pub fn invalidate_all(&mut self) {
+		for chunk in &mut self.chunks {
+			chunk.dirty = true;
+		}
+	}
+	/// Drops all cached geometry, eg. when a new level is loaded or the resources are reloaded.
+	pub fn clear(&mut self) {
+		*self = TerrainCache::default();
+	}
+
+	/// Rebuilds the chunks whose terrain changed.
+	pub fn update(&mut self, field: &core::Field, tiles: &[TileGfx], atlas: &SpriteAtlas) {
+		let size = Vec2(field.width, field.height).map(|c| (c + CHUNK_SIZE - 1) / CHUNK_SIZE);
+		// Everything is rebuilt when the map or the way it is drawn changes
+		if size != self.size || self.width != field.width || self.terrain.len() != field.terrain.len() || self.tiles != tiles || self.atlas != *atlas {
+			self.size = size;
+			self.width = field.width;
+			self.chunks.clear();
+			self.chunks.resize_with((size.x * size.y) as usize, || Chunk { dirty: true, ..Chunk::default() });
+			self.terrain.clear();
+			self.terrain.resize(field.terrain.len(), core::Terrain::Blank);
+			self.tiles = tiles.to_vec();
+			self.atlas.clone_from(atlas);
+			for y in 0..size.y {
+				for x in 0..size.x {
+					self.build_chunk(Vec2(x, y), field, atlas);
+				}
+			}
+			return;
+		}
+
+		for y in 0..size.y {
+			for x in 0..size.x {
+				let index = (y * size.x + x) as usize;
+				if !self.chunks[index].dirty {
+					continue;
+				}
+				self.chunks[index].dirty = false;
+				if self.chunk_changed(Vec2(x, y), field) {
+					self.build_chunk(Vec2(x, y), field, atlas);
+				}
+			}
+		}
+	}
+
+	fn chunk_tiles(&self, chunk: Vec2<i32>, field: &core::Field) -> (Vec2<i32>, Vec2<i32>) {
+		let min = chunk * CHUNK_SIZE;
+		let max = Vec2(i32::min(min.x + CHUNK_SIZE, field.width), i32::min(min.y + CHUNK_SIZE, field.height));
+		(min, max)
+	}
+
+	fn chunk_changed(&self, chunk: Vec2<i32>, field: &core::Field) -> bool {
+		let (min, max) = self.chunk_tiles(chunk, field);
+		(min.y..max.y).any(|y| {
+			let start = (y * field.width + min.x) as usize;
+			let end = (y * field.width + max.x) as usize;
+			self.terrain[start..end] != field.terrain[start..end]
+		})
+	}
+
+	fn build_chunk(&mut self, chunk: Vec2<i32>, field: &core::Field, atlas: &SpriteAtlas) {
+		let (min, max) = self.chunk_tiles(chunk, field);
+		let index = (chunk.y * self.size.x + chunk.x) as usize;
+		let Chunk { mesh, animated, dirty } = &mut self.chunks[index];
+		mesh.clear();
+		animated.clear();
+		*dirty = false;
+
+		for y in min.y..max.y {
+			for x in min.x..max.x {
+				let tile_index = (y * field.width + x) as usize;
+				let terrain = field.terrain[tile_index];
+				self.terrain[tile_index] = terrain;
+
+				let tile = self.tiles[terrain as usize];
+				if tile.sprite == Sprite::Blank || tile.model == Model::Empty {
+					continue;
+				}
+				if atlas.is_animated(tile.sprite) {
+					animated.push((Vec2(x, y), tile));
+					continue;
+				}
+				render::draw_terrain(mesh, atlas, 0.0, Vec2(x, y), tile);
+			}
+		}
+	}
+
+	/// Draws the chunks inside the view frustum, animated tiles are drawn at the given time.
+	pub fn draw(&self, cv: &mut impl PrimSink, frustum: &Frustum, atlas: &SpriteAtlas, time: f32) {
+		let chunk_world = CHUNK_SIZE as f32 * 32.0;
+		for y in 0..self.size.y {
+			for x in 0..self.size.x {
+				// Portals dip below the floor and walls rise above it
+				let min = Vec3(x as f32 * chunk_world, y as f32 * chunk_world, -10.0);
+				let max = Vec3(min.x + chunk_world, min.y + chunk_world, 32.0);
+				if !frustum.contains_box(min, max) {
+					continue;
+				}
+				let chunk = &self.chunks[(y * self.size.x + x) as usize];
+				chunk.mesh.draw(cv);
+				for &(pos, tile) in &chunk.animated {
+					render::draw_terrain(cv, atlas, time, pos, tile);
+				}
+			}
+		}
+	}
+}
@@ -7,98 +7,66 @@ pub struct TileGfx {
 	pub model: Model,
 }
 
-pub static TILES_PLAY: [TileGfx; 45] = [
-	TileGfx { sprite: Sprite::Blank, model: Model::Empty }, // Terrain::Blank
-	TileGfx { sprite: Sprite::Floor, model: Model::Floor }, // Terrain::Floor
-	TileGfx { sprite: Sprite::Wall, model: Model::Wall }, // Terrain::Wall
-	TileGfx { sprite: Sprite::Floor, model: Model::Floor }, // Terrain::Socket
-	TileGfx { sprite: Sprite::BlueLock, model: Model::Wall }, // Terrain::BlueLock
-	TileGfx { sprite: Sprite::RedLock, model: Model::Wall }, // Terrain::RedLock
-	TileGfx { sprite: Sprite::GreenLock, model: Model::Wall }, // Terrain::GreenLock
-	TileGfx { sprite: Sprite::YellowLock, model: Model::Wall }, // Terrain::YellowLock
-	TileGfx { sprite: Sprite::Hint, model: Model::Floor }, // Terrain::Hint
-	TileGfx { sprite: Sprite::Exit1, model: Model::Portal }, // Terrain::Exit
-	TileGfx { sprite: Sprite::Water, model: Model::Floor }, // Terrain::Water
-	TileGfx { sprite: Sprite::Floor, model: Model::Floor }, // Terrain::Fire
-	TileGfx { sprite: Sprite::Dirt, model: Model::Floor }, // Terrain::Dirt
-	TileGfx { sprite: Sprite::Gravel, model: Model::Floor }, // Terrain::Gravel
-	TileGfx { sprite: Sprite::Ice, model: Model::Floor }, // Terrain::Ice
-	TileGfx { sprite: Sprite::IceUL, model: Model::Floor }, // Terrain::IceNW
-	TileGfx { sprite: Sprite::IceUR, model: Model::Floor }, // Terrain::IceNE
-	TileGfx { sprite: Sprite::IceDL, model: Model::Floor }, // Terrain::IceSW
-	TileGfx { sprite: Sprite::IceDR, model: Model::Floor }, // Terrain::IceSE
-	TileGfx { sprite: Sprite::ForceUp, model: Model::Floor }, // Terrain::ForceN
-	TileGfx { sprite: Sprite::ForceLeft, model: Model::Floor }, // Terrain::ForceW
-	TileGfx { sprite: Sprite::ForceDown, model: Model::Floor }, // Terrain::ForceS
-	TileGfx { sprite: Sprite::ForceRight, model: Model::Floor }, // Terrain::ForceE
-	TileGfx { sprite: Sprite::ForceRandom, model: Model::Floor }, // Terrain::ForceRandom
-	TileGfx { sprite: Sprite::CloneMachine, model: Model::Wall }, // Terrain::CloneMachine
-	TileGfx { sprite: Sprite::OnOffFloor, model: Model::Floor }, // Terrain::ToggleFloor
-	TileGfx { sprite: Sprite::OnOffFloor, model: Model::Floor }, // Terrain::ToggleWall
-	TileGfx { sprite: Sprite::PanelNorth, model: Model::Floor }, // Terrain::PanelN
-	TileGfx { sprite: Sprite::PanelWest, model: Model::Floor }, // Terrain::PanelW
-	TileGfx { sprite: Sprite::PanelSouth, model: Model::Floor }, // Terrain::PanelS
-	TileGfx { sprite: Sprite::PanelEast, model: Model::Floor }, // Terrain::PanelE
-	TileGfx { sprite: Sprite::PanelSE, model: Model::Floor }, // Terrain::PanelSE
-	TileGfx { sprite: Sprite::Floor, model: Model::Floor }, // Terrain::HiddenWall
-	TileGfx { sprite: Sprite::Floor, model: Model::Floor }, // Terrain::HiddenWallRevealed
-	TileGfx { sprite: Sprite::Floor, model: Model::Floor }, // Terrain::InvisWall
-	TileGfx { sprite: Sprite::BlueWall, model: Model::Wall }, // Terrain::BlueWall
-	TileGfx { sprite: Sprite::BlueWall, model: Model::Wall }, // Terrain::BlueFake
-	TileGfx { sprite: Sprite::GreenSwitch, model: Model::Floor }, // Terrain::GreenButton
-	TileGfx { sprite: Sprite::RedSwitch, model: Model::Floor }, // Terrain::RedButton
-	TileGfx { sprite: Sprite::BrownSwitch, model: Model::Floor }, // Terrain::BrownButton
-	TileGfx { sprite: Sprite::BlueSwitch, model: Model::Floor }, // Terrain::BlueButton
-	TileGfx { sprite: Sprite::Teleport, model: Model::Floor }, // Terrain::Teleport
-	TileGfx { sprite: Sprite::BearTrap, model: Model::Floor }, // Terrain::BearTrap
-	TileGfx { sprite: Sprite::RecessedWall, model: Model::Floor }, // Terrain::RecessedWall
-	TileGfx { sprite: Sprite::RecessedWall, model: Model::Floor }, // Terrain::RaisedWall
-];
+#[derive(serde::Deserialize)]
+struct TerrainGfxDto {
+	terrain: core::Terrain,
+	play: TileGfx,
+	edit: TileGfx,
+}
+
+#[derive(serde::Deserialize)]
+struct DataDto {
+	terrain: Vec<TerrainGfxDto>,
+}
 
-pub static TILES_EDIT: [TileGfx; 45] = [
-	TileGfx { sprite: Sprite::Blank, model: Model::Empty }, // Terrain::Blank
-	TileGfx { sprite: Sprite::Floor, model: Model::Floor }, // Terrain::Floor
-	TileGfx { sprite: Sprite::Wall, model: Model::Wall }, // Terrain::Wall
-	TileGfx { sprite: Sprite::Floor, model: Model::Floor }, // Terrain::Socket
-	TileGfx { sprite: Sprite::BlueLock, model: Model::Wall }, // Terrain::BlueLock
-	TileGfx { sprite: Sprite::RedLock, model: Model::Wall }, // Terrain::RedLock
-	TileGfx { sprite: Sprite::GreenLock, model: Model::Wall }, // Terrain::GreenLock
-	TileGfx { sprite: Sprite::YellowLock, model: Model::Wall }, // Terrain::YellowLock
-	TileGfx { sprite: Sprite::Hint, model: Model::Floor }, // Terrain::Hint
-	TileGfx { sprite: Sprite::Exit1, model: Model::Portal }, // Terrain::Exit
-	TileGfx { sprite: Sprite::Water, model: Model::Floor }, // Terrain::Water
-	TileGfx { sprite: Sprite::Floor, model: Model::Floor }, // Terrain::Fire
-	TileGfx { sprite: Sprite::Dirt, model: Model::Floor }, // Terrain::Dirt
-	TileGfx { sprite: Sprite::Gravel, model: Model::Floor }, // Terrain::Gravel
-	TileGfx { sprite: Sprite::Ice, model: Model::Floor }, // Terrain::Ice
-	TileGfx { sprite: Sprite::IceUL, model: Model::Floor }, // Terrain::IceNW
-	TileGfx { sprite: Sprite::IceUR, model: Model::Floor }, // Terrain::IceNE
-	TileGfx { sprite: Sprite::IceDL, model: Model::Floor }, // Terrain::IceSW
-	TileGfx { sprite: Sprite::IceDR, model: Model::Floor }, // Terrain::IceSE
-	TileGfx { sprite: Sprite::ForceUp, model: Model::Floor }, // Terrain::ForceN
-	TileGfx { sprite: Sprite::ForceLeft, model: Model::Floor }, // Terrain::ForceW
-	TileGfx { sprite: Sprite::ForceDown, model: Model::Floor }, // Terrain::ForceS
-	TileGfx { sprite: Sprite::ForceRight, model: Model::Floor }, // Terrain::ForceE
-	TileGfx { sprite: Sprite::ForceRandom, model: Model::Floor }, // Terrain::ForceRandom
-	TileGfx { sprite: Sprite::CloneMachine, model: Model::Wall }, // Terrain::CloneMachine
-	TileGfx { sprite: Sprite::OnOffFloor, model: Model::Floor }, // Terrain::ToggleFloor
-	TileGfx { sprite: Sprite::OnOffWall, model: Model::Wall }, // Terrain::ToggleWall
-	TileGfx { sprite: Sprite::PanelNorth, model: Model::Floor }, // Terrain::PanelN
-	TileGfx { sprite: Sprite::PanelWest, model: Model::Floor }, // Terrain::PanelW
-	TileGfx { sprite: Sprite::PanelSouth, model: Model::Floor }, // Terrain::PanelS
-	TileGfx { sprite: Sprite::PanelEast, model: Model::Floor }, // Terrain::PanelE
-	TileGfx { sprite: Sprite::PanelSE, model: Model::Floor }, // Terrain::PanelSE
-	TileGfx { sprite: Sprite::HiddenWall, model: Model::Wall }, // Terrain::HiddenWall
-	TileGfx { sprite: Sprite::HiddenWall, model: Model::Wall }, // Terrain::HiddenWallRevealed
-	TileGfx { sprite: Sprite::InvisWall, model: Model::Wall }, // Terrain::InvisWall
-	TileGfx { sprite: Sprite::BlueWall, model: Model::Wall }, // Terrain::BlueWall
-	TileGfx { sprite: Sprite::BlueWallFake, model: Model::Wall }, // Terrain::BlueFake
-	TileGfx { sprite: Sprite::GreenSwitch, model: Model::Floor }, // Terrain::GreenButton
-	TileGfx { sprite: Sprite::RedSwitch, model: Model::Floor }, // Terrain::RedButton
-	TileGfx { sprite: Sprite::BrownSwitch, model: Model::Floor }, // Terrain::BrownButton
-	TileGfx { sprite: Sprite::BlueSwitch, model: Model::Floor }, // Terrain::BlueButton
-	TileGfx { sprite: Sprite::Teleport, model: Model::Floor }, // Terrain::Teleport
-	TileGfx { sprite: Sprite::BearTrap, model: Model::Floor }, // Terrain::BearTrap
-	TileGfx { sprite: Sprite::RecessedWall, model: Model::Floor }, // Terrain::RecessedWall
-	TileGfx { sprite: Sprite::RecessedWall, model: Model::Floor }, // Terrain::RaisedWall
-];
+/// Terrain visuals indexed by terrain, for the play view and the editor.
+#[derive(Clone, Debug)]
+pub struct TileSet {
+	pub play: Vec<TileGfx>,
+	pub edit: Vec<TileGfx>,
+}
+
+static BUILTIN_TILES: std::sync::OnceLock<TileSet> = std::sync::OnceLock::new();
+
+impl Default for TileSet {
+	fn default() -> Self {
+		BUILTIN_TILES.get_or_init(|| TileSet::parse(include_str!("../../data/data.json")).unwrap()).clone()
+	}
+}
+
+impl TileSet {
+	/// Parses the `terrain` section of the data file.
+	pub fn parse(json: &str) -> Result<TileSet, String> {
+		let dto: DataDto = serde_json::from_str(json).map_err(|err| err.to_string())?;
+
+		let mut slots = [None; core::TERRAIN_COUNT];
+		for tile in &dto.terrain {
+			let slot = &mut slots[tile.terrain as usize];
+			if slot.is_some() {
+				return Err(format!("Duplicate terrain definition: {:?}", tile.terrain));
+			}
+			for gfx in [tile.play, tile.edit] {
+				if !is_tile_model(gfx.model) {
+					return Err(format!("Unsupported model for {:?}: {:?}", tile.terrain, gfx.model));
+				}
+			}
+			*slot = Some((tile.play, tile.edit));
+		}
+
+		let mut play = Vec::with_capacity(core::TERRAIN_COUNT);
+		let mut edit = Vec::with_capacity(core::TERRAIN_COUNT);
+		for &terrain in core::ALL_TERRAIN.iter() {
+			let Some((play_gfx, edit_gfx)) = slots[terrain as usize] else {
+				return Err(format!("Missing terrain definition: {:?}", terrain));
+			};
+			play.push(play_gfx);
+			edit.push(edit_gfx);
+		}
+		Ok(TileSet { play, edit })
+	}
+}
+
+/// Returns if terrain can be drawn with the model.
+fn is_tile_model(model: Model) -> bool {
+	!matches!(model, Model::WallV2 | Model::SpriteShadow)
+}
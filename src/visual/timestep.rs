@@ -0,0 +1,57 @@
+use super::*;
+
+pub use crate::core::TICK_RATE;
+
+/// Accumulates real time and hands out game ticks at a fixed rate.
+///
+/// The time left over between ticks is exposed as the interpolation alpha for rendering.
+#[derive(Clone, Debug)]
+pub struct FixedTimestep {
+	accumulator: f32,
+	step: f32,
+	/// Real time beyond which ticks are dropped so a stall does not make the game race to catch up.
+	max_elapsed: f32,
+}
+
+impl Default for FixedTimestep {
+	fn default() -> Self {
+		FixedTimestep::new(TICK_RATE)
+	}
+}
+
+impl FixedTimestep {
+	pub fn new(rate: f32) -> FixedTimestep {
+		FixedTimestep { accumulator: 0.0, step: 1.0 / rate, max_elapsed: 0.25 }
+	}
+	/// Adds the real elapsed time in seconds and returns the number of ticks to run.
+	pub fn advance(&mut self, elapsed: f32) -> u32 {
+		self.accumulator += f32::min(f32::max(elapsed, 0.0), self.max_elapsed);
+		let mut ticks = 0;
+		while self.accumulator >= self.step {
+			self.accumulator -= self.step;
+			ticks += 1;
+		}
+		ticks
+	}
+	/// Fraction of a tick elapsed since the last tick, in the range 0.0 to 1.0.
+	pub fn alpha(&self) -> f32 {
+		self.accumulator / self.step
+	}
+	pub fn reset(&mut self) {
+		self.accumulator = 0.0;
+	}
+}
+
+impl VisualState {
+	/// Advances the game by the real elapsed time in seconds.
+	///
+	/// The game ticks at the fixed rate as many times as fit in the elapsed time, the rendering interpolates the remainder.
+	pub fn advance(&mut self, input: &core::Input, elapsed: f32) {
+		let ticks = self.timestep.advance(elapsed);
+		for _ in 0..ticks {
+			self.update(input);
+		}
+		self.alpha = self.timestep.alpha();
+		self.dt = elapsed;
+	}
+}
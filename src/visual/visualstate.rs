@@ -2,44 +2,78 @@ use super::*;
 
 #[derive(Default)]
 pub struct VisualState {
+	/// Time in seconds interpolated between game ticks.
 	pub time: f32,
+	/// Real time in seconds since the last frame.
 	pub dt: f32,
+	/// Fraction of a tick elapsed since the last game tick.
+	pub alpha: f32,
+	pub timestep: FixedTimestep,
 	pub game: core::GameState,
 	pub camera: Camera,
 	pub objects: ObjectMap,
+	pub terrain_cache: TerrainCache,
+	pub effects: Effects,
+	pub hud: Hud,
 	pub resources: Resources,
-	pub tiles: &'static [TileGfx],
+	pub tiles: Vec<TileGfx>,
 }
 
 impl VisualState {
 	pub fn init(&mut self) {
-		self.tiles = &TILES_PLAY;
+		self.tiles = TileSet::default().play;
+	}
+	pub fn load_data(&mut self, json: &str) -> Result<(), String> {
+		let defs = core::TerrainDefs::parse(json)?;
+		let tiles = TileSet::parse(json)?;
+		self.game.field.defs = defs;
+		self.tiles = tiles.play;
+		Ok(())
 	}
 	pub fn load_level(&mut self, json: &str) {
-		self.game.load(json);
-		self.sync(&self.game.events.clone());
+		self.load_dto(core::dto::LevelDto::parse(json).unwrap());
 		self.camera.eye_offset = Vec3::new(0.0, 2.0 * 32.0, 400.0);
-
+	}
+	/// Loads the level replacing the current level and its objects.
+	pub fn load_dto(&mut self, ld: core::dto::LevelDto) {
+		self.objects = ObjectMap::default();
+		self.terrain_cache.clear();
+		self.effects.clear();
+		self.hud.hint_pos = None;
+		self.timestep.reset();
+		self.alpha = 0.0;
+		self.game.load_dto(ld);
+		self.sync(&self.game.events.clone());
+		self.create_terrain_objects();
+	}
+	fn create_terrain_objects(&mut self) {
 		for y in 0..self.game.field.height {
 			for x in 0..self.game.field.width {
-				let index = (y * self.game.field.width + x) as usize;
-				let terrain = self.game.field.terrain[index];
-				match terrain {
-					core::Terrain::Fire => create_fire(self, Vec2::new(x, y)),
-					core::Terrain::ToggleFloor => create_toggle_floor(self, Vec2(x, y)),
-					core::Terrain::ToggleWall => create_toggle_wall(self, Vec2(x, y)),
-					_ => {}
-				}
+				self.create_terrain_object(Vec2(x, y));
 			}
 		}
 	}
+	/// Creates the object drawn on top of the terrain at the tile, if any.
+	pub fn create_terrain_object(&mut self, pos: Vec2<i32>) {
+		match self.game.field.get_terrain(pos) {
+			core::Terrain::Fire => create_fire(self, pos),
+			core::Terrain::ToggleFloor => create_toggle_floor(self, pos),
+			core::Terrain::ToggleWall => create_toggle_wall(self, pos),
+			_ => {}
+		}
+	}
+	/// Removes the objects drawn on top of the terrain at the tile, eg. when the terrain is edited.
+	pub fn remove_terrain_objects(&mut self, pos: Vec2<i32>) {
+		let origin = pos.map(|c| c as f32 * 32.0);
+		self.objects.map.retain(|_, obj| obj.entity_handle != core::EntityHandle::default() || obj.pos.xy() != origin);
+	}
 	pub fn update(&mut self, input: &core::Input) {
 		self.game.tick(input);
 		self.sync(&self.game.events.clone());
 	}
 	pub fn sync(&mut self, events: &Vec<core::GameEvent>) {
 		for ev in events {
-			println!("Event: {:?}", ev);
+			self.invalidate_terrain(ev);
 			match ev {
 				&core::GameEvent::EntityCreated { entity } => entity_created(self, entity),
 				&core::GameEvent::EntityRemoved { entity } => entity_removed(self, entity),
@@ -54,14 +88,38 @@ impl VisualState {
 				&core::GameEvent::HiddenWallBumped { pos } => hidden_wall_bumped(self, pos),
 				&core::GameEvent::RecessedWallRaised { pos } => recessed_wall_raised(self, pos),
 				&core::GameEvent::GreenButton { .. } => toggle_walls(self),
+				&core::GameEvent::BombExplode { entity } => bomb_explode(self, entity),
+				&core::GameEvent::EntityDrown { entity } => entity_drown(self, entity),
+				&core::GameEvent::SocketFilled { pos } => socket_filled(self, pos),
+				&core::GameEvent::ItemsThief { player } => items_thief(self, player),
+				&core::GameEvent::BlueWallBumped { pos } => blue_wall_bumped(self, pos),
+				&core::GameEvent::PlayerHint { pos, .. } => player_hint(self, pos),
 				_ => {}
 			}
 		}
 	}
-	pub fn draw(&mut self, g: &mut shade::Graphics) {
-		let time = self.game.time as f32 / 60.0;
+	/// Marks the cached terrain geometry around terrain changes.
+	fn invalidate_terrain(&mut self, ev: &core::GameEvent) {
+		match ev {
+			&core::GameEvent::LockRemoved { pos, .. }
+			| &core::GameEvent::BlueWallCleared { pos }
+			| &core::GameEvent::BlueWallBumped { pos }
+			| &core::GameEvent::HiddenWallBumped { pos }
+			| &core::GameEvent::RecessedWallRaised { pos }
+			| &core::GameEvent::SocketFilled { pos } => self.terrain_cache.invalidate(pos),
+			&core::GameEvent::GreenButton { .. } | &core::GameEvent::BlueButton { .. } => self.terrain_cache.invalidate_all(),
+			// Blocks turn water to dirt as they are removed
+			&core::GameEvent::EntityRemoved { entity } => {
+				if let Some(obj) = self.objects.lookup.get(&entity).and_then(|&handle| self.objects.get(handle)) {
+					self.terrain_cache.invalidate(obj.pos.xy().map(|c| f32::floor(c / 32.0 + 0.5) as i32));
+				}
+			}
+			_ => {}
+		}
+	}
+	pub fn draw(&mut self, g: &mut impl DrawBackend) {
+		let time = (self.game.time as f32 + self.alpha) / TICK_RATE;
 		self.time = time;
-		self.dt = 1.0 / 60.0;
 		let size = self.resources.screen_size;
 
 		for handle in self.objects.map.keys().cloned().collect::<Vec<_>>() {
@@ -69,29 +127,33 @@ impl VisualState {
 			obj.update(self);
 			self.objects.insert(obj);
 		}
+		self.effects.update(self.dt);
 
-		g.begin().unwrap();
+		g.begin();
 
 		// Clear the screen
-		g.clear(&shade::ClearArgs {
-			surface: shade::Surface::BACK_BUFFER,
-			color: Some(cvmath::Vec4(0.2, 0.2, 0.5, 1.0)),
-			depth: Some(1.0),
-			..Default::default()
-		}).unwrap();
+		g.clear(Some(cvmath::Vec4(0.2, 0.2, 0.5, 1.0)));
 
 		self.set_game_camera();
+		// Entities change the terrain under them without an event, eg. dirt turns to floor
+		for ent in self.game.ents.map.values() {
+			self.terrain_cache.invalidate(ent.pos);
+		}
+		self.terrain_cache.update(&self.game.field, &self.tiles, &self.resources.atlas);
+
+		g.begin_pass(&DrawPass {
+			shader: self.resources.shader,
+			uniform: self.resources.tileset_uniform(self.camera.view_proj_mat),
+			depth_test: true,
+			blend: Blend::Solid,
+			viewport: size,
+		});
+		render::field(g, self, time);
+		g.end_pass();
 
-		let mut cv = shade::d2::Canvas::<render::Vertex, render::Uniform>::new();
-		cv.shader = self.resources.shader;
-		cv.depth_test = Some(shade::DepthTest::Less);
-		cv.viewport = cvmath::Rect::vec(cvmath::Vec2(size.x as i32, size.y as i32));
-		// cv.cull_mode = Some(shade::CullMode::CW);
-		cv.push_uniform(render::Uniform { transform: self.camera.view_proj_mat, texture: self.resources.tileset, texture_size: self.resources.tileset_size.map(|c| c as f32).into() });
-		render::field(&mut cv, self, time);
-		cv.draw(g, shade::Surface::BACK_BUFFER).unwrap();
+		self.draw_hud(g);
 
-		g.end().unwrap();
+		g.end();
 
 		self.objects.map.retain(|_, obj| obj.live);
 	}
@@ -0,0 +1,61 @@
+use chipgame::bindings::*;
+
+#[test]
+fn default_bindings() {
+	let mut map = InputMap::new(Bindings::parse(include_str!("../data/bindings.json")).unwrap());
+	assert_eq!(map.bindings, Bindings::default());
+
+	// Every action has a keyboard and a gamepad binding
+	map.key("Left", true);
+	map.button(GamepadButton::DPadRight, true);
+	let input = map.input();
+	assert!(input.left && input.right && !input.up && !input.down);
+
+	map.key("Left", false);
+	map.button(GamepadButton::DPadRight, false);
+	map.key("Up", true);
+	assert!(map.input().up);
+	map.release_all();
+	assert!(!map.is_active(Action::Up));
+}
+
+#[test]
+fn axis_deadzone() {
+	let mut map = InputMap::default();
+	map.axis(GamepadAxis::LeftStickX, -0.3);
+	assert!(!map.is_active(Action::Left));
+	map.axis(GamepadAxis::LeftStickX, -0.8);
+	assert!(map.is_active(Action::Left) && !map.is_active(Action::Right));
+
+	// The stick's y axis points up
+	map.axis(GamepadAxis::LeftStickY, 0.9);
+	assert!(map.is_active(Action::Up) && !map.is_active(Action::Down));
+}
+
+#[test]
+fn chords() {
+	let mut map = InputMap::default();
+	map.key("A", true);
+	assert!(map.input().a && !map.input().dev_wtw);
+	map.key("B", true);
+	assert!(map.input().dev_wtw);
+	map.key("A", false);
+	assert!(!map.input().dev_wtw);
+}
+
+#[test]
+fn custom_bindings() {
+	let json = r#"{ "deadzone": 0.25, "actions": { "Up": [{ "Key": "W" }, { "Key": "Up" }] } }"#;
+	let mut map = InputMap::new(Bindings::parse(json).unwrap());
+	map.key("W", true);
+	assert!(map.input().up);
+	// Unbound actions are never active
+	map.key("Left", true);
+	assert!(!map.input().left);
+
+	assert!(Bindings::parse(r#"{ "deadzone": 1.5, "actions": {} }"#).is_err());
+	assert!(Bindings::parse(r#"{ "deadzone": 0.5, "actions": { "A": [{ "Chord": [] }] } }"#).is_err());
+	assert!(Bindings::parse(r#"{ "deadzone": 0.5, "actions": { "A": [{ "Chord": [{ "Key": "A" }, { "Chord": [] }] }] } }"#).is_err());
+	assert!(Bindings::parse(r#"{ "deadzone": 0.5, "actions": { "A": [{ "Chord": [{ "Chord": [] }] }] } }"#).is_err());
+	assert!(Bindings::parse(r#"{ "deadzone": 0.5, "actions": { "Jump": [] } }"#).is_err());
+}
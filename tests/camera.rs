@@ -0,0 +1,76 @@
+use chipgame::visual::{Camera, Projection};
+use cvmath::{Vec2, Vec3};
+
+const SCREEN: Vec2<i32> = Vec2 { x: 800, y: 600 };
+
+fn camera(projection: Projection) -> Camera {
+	let mut camera = Camera::default();
+	camera.target = Vec3(160.0, 240.0, 0.0);
+	camera.target_fast = camera.target;
+	camera.eye_offset = Vec3(0.0, 8.0 * 32.0, 400.0);
+	camera.projection = projection;
+	// A long frame settles the projection blend
+	camera.update(SCREEN, 10.0);
+	camera
+}
+
+/// Positions are within half a pixel, unprojecting on the near plane is not any more precise.
+fn assert_near(a: Vec2<f32>, b: Vec2<f32>) {
+	assert!((a.x - b.x).abs() < 0.5 && (a.y - b.y).abs() < 0.5, "{:?} != {:?}", a, b);
+}
+
+#[test]
+fn screen_round_trip() {
+	for projection in [Projection::Perspective, Projection::Orthographic] {
+		let camera = camera(projection);
+		for pixel in [Vec2(400, 300), Vec2(0, 0), Vec2(799, 599), Vec2(123, 456)] {
+			let expected = pixel.map(|c| c as f32);
+
+			// The ray starts on the pixel and points away from the eye
+			let ray = camera.screen_to_ray(pixel);
+			assert_near(camera.world_to_screen(ray.origin).unwrap(), expected);
+			assert_near(camera.world_to_screen(ray.at(100.0)).unwrap(), expected);
+
+			let world = camera.screen_to_world(pixel).unwrap();
+			assert!(world.z.abs() < 0.01);
+			assert_near(camera.world_to_screen(world).unwrap(), expected);
+		}
+
+		// Tile centers project to pixels inside the same tile
+		for tile in [Vec2(5, 7), Vec2(3, 4), Vec2(8, 9)] {
+			let center = tile.map(|c| c as f32 * 32.0 + 16.0).vec3(0.0);
+			let pixel = camera.world_to_screen(center).unwrap().map(|c| c.round() as i32);
+			assert_eq!(camera.screen_to_tile(pixel), Some(tile), "{:?}", projection);
+		}
+	}
+}
+
+#[test]
+fn behind_camera() {
+	let camera = camera(Projection::Perspective);
+	assert_eq!(camera.world_to_screen(camera.eye() + (camera.eye() - camera.target)), None);
+}
+
+#[test]
+fn pan_drag() {
+	for projection in [Projection::Perspective, Projection::Orthographic] {
+		let mut camera = camera(projection);
+		let (from, to) = (Vec2(400, 300), Vec2(450, 280));
+		let ground = camera.screen_to_world(from).unwrap();
+		camera.pan_drag(from, to);
+		camera.update(SCREEN, 10.0);
+
+		// The ground under the mouse moves with the mouse
+		assert_near(camera.world_to_screen(ground).unwrap(), to.map(|c| c as f32));
+		assert_eq!(camera.target, Vec3(160.0, 240.0, 0.0));
+	}
+}
+
+#[test]
+fn zoom_limits() {
+	let mut camera = Camera::default();
+	camera.zoom_by(100.0);
+	assert_eq!(camera.zoom, 4.0);
+	camera.zoom_by(0.001);
+	assert_eq!(camera.zoom, 0.5);
+}
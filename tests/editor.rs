@@ -0,0 +1,503 @@
+use chipgame::core::{self, dto};
+use std::fs;
+use chipgame::editor::{mirror_terrain, rotate_terrain, Clipboard, Command, EditorDocument, EditorGame, EditorInput, History, TextField, Tool};
+use chipgame::visual::{Recorder, Resources, TileSet, VisualState};
+use cvmath::Vec2;
+
+fn chip(pos: Vec2<i32>) -> core::EntityArgs {
+	core::EntityArgs { kind: core::EntityKind::Chip, pos, face_dir: None }
+}
+
+#[test]
+fn paint_undo_redo() {
+	let mut doc = EditorDocument::new(4, 4);
+	doc.begin();
+	doc.apply_tool(Tool::Terrain(core::Terrain::Wall), Vec2(1, 1));
+	doc.apply_tool(Tool::Terrain(core::Terrain::Wall), Vec2(2, 1));
+	doc.end();
+	doc.apply_tool(Tool::Terrain(core::Terrain::Water), Vec2(3, 3));
+
+	assert_eq!(doc.map().get_terrain(Vec2(1, 1)), core::Terrain::Wall);
+	assert_eq!(doc.map().get_terrain(Vec2(3, 3)), core::Terrain::Water);
+
+	assert!(doc.undo());
+	assert_eq!(doc.map().get_terrain(Vec2(3, 3)), core::Terrain::Floor);
+	assert_eq!(doc.map().get_terrain(Vec2(2, 1)), core::Terrain::Wall);
+
+	// The grouped stroke is undone as a whole
+	assert!(doc.undo());
+	assert_eq!(doc.map().get_terrain(Vec2(1, 1)), core::Terrain::Floor);
+	assert_eq!(doc.map().get_terrain(Vec2(2, 1)), core::Terrain::Floor);
+	assert!(!doc.undo());
+
+	assert!(doc.redo());
+	assert_eq!(doc.map().get_terrain(Vec2(1, 1)), core::Terrain::Wall);
+	assert_eq!(doc.map().get_terrain(Vec2(2, 1)), core::Terrain::Wall);
+}
+
+#[test]
+fn nested_groups_undo() {
+	let mut doc = EditorDocument::new(4, 4);
+	doc.apply_tool(Tool::Entity(chip(Vec2::ZERO)), Vec2(2, 2));
+
+	// Erasing groups its own commands, inside a stroke the stroke is a single group
+	doc.begin();
+	doc.apply_tool(Tool::Terrain(core::Terrain::Wall), Vec2(1, 1));
+	doc.apply_tool(Tool::Erase, Vec2(2, 2));
+	doc.apply_tool(Tool::Terrain(core::Terrain::Wall), Vec2(3, 3));
+	doc.end();
+	assert_eq!(doc.count_chips(), 0);
+
+	assert!(doc.undo());
+	assert_eq!(doc.map().get_terrain(Vec2(1, 1)), core::Terrain::Floor);
+	assert_eq!(doc.map().get_terrain(Vec2(3, 3)), core::Terrain::Floor);
+	assert_eq!(doc.count_chips(), 1);
+
+	assert!(doc.redo());
+	assert_eq!(doc.map().get_terrain(Vec2(3, 3)), core::Terrain::Wall);
+	assert_eq!(doc.count_chips(), 0);
+
+	let mut history = History::new(10);
+	let cmd = |x| Command::SetTerrain { pos: Vec2(x, 0), old: core::Terrain::Floor, new: core::Terrain::Wall };
+	history.begin();
+	history.push(cmd(0));
+	history.begin();
+	history.push(cmd(1));
+	history.end();
+	history.push(cmd(2));
+	history.end();
+	history.push(cmd(3));
+	assert_eq!(history.undo().map(|group| group.len()), Some(1));
+	assert_eq!(history.undo().map(|group| group.len()), Some(3));
+	assert!(!history.can_undo());
+}
+
+#[test]
+fn erase_entities() {
+	let mut doc = EditorDocument::new(4, 4);
+	doc.apply_tool(Tool::Entity(chip(Vec2::ZERO)), Vec2(1, 2));
+	doc.apply_tool(Tool::Entity(chip(Vec2::ZERO)), Vec2(1, 2));
+	doc.apply_tool(Tool::Entity(chip(Vec2::ZERO)), Vec2(3, 0));
+	assert_eq!(doc.count_chips(), 3);
+
+	doc.apply_tool(Tool::Erase, Vec2(1, 2));
+	assert_eq!(doc.count_chips(), 1);
+	assert_eq!(doc.to_dto().entities.len(), 1);
+
+	// Undoing restores the entities in their original order
+	assert!(doc.undo());
+	let positions = doc.map().entities.iter().map(|args| args.pos).collect::<Vec<_>>();
+	assert_eq!(positions, [Vec2(1, 2), Vec2(1, 2), Vec2(3, 0)]);
+}
+
+#[test]
+fn connect_toggles() {
+	let mut doc = EditorDocument::new(4, 4);
+	doc.connect(Vec2(0, 0), Vec2(3, 3));
+	assert_eq!(doc.map().conns, [core::Connection { src: Vec2(0, 0), dest: Vec2(3, 3) }]);
+
+	doc.connect(Vec2(0, 0), Vec2(3, 3));
+	assert!(doc.map().conns.is_empty());
+
+	// Connecting a tile to itself does nothing
+	doc.connect(Vec2(1, 1), Vec2(1, 1));
+	assert!(doc.map().conns.is_empty());
+
+	assert!(doc.undo());
+	assert_eq!(doc.map().conns.len(), 1);
+}
+
+#[test]
+fn resize_moves_contents() {
+	let mut doc = EditorDocument::new(4, 4);
+	doc.set_terrain(Vec2(0, 0), core::Terrain::Wall);
+	doc.place_entity(chip(Vec2(3, 3)));
+	doc.connect(Vec2(0, 0), Vec2(3, 3));
+
+	assert!(doc.resize(1, 2, 0, 0));
+	assert_eq!((doc.map().width, doc.map().height), (5, 6));
+	assert_eq!(doc.map().get_terrain(Vec2(1, 2)), core::Terrain::Wall);
+	assert_eq!(doc.map().entities[0].pos, Vec2(4, 5));
+	assert_eq!(doc.map().conns[0].dest, Vec2(4, 5));
+
+	// Shrinking removes what falls off the map
+	assert!(doc.resize(0, 0, -1, 0));
+	assert!(doc.map().entities.is_empty());
+	assert!(doc.map().conns.is_empty());
+
+	assert!(!doc.resize(-4, 0, 0, 0));
+
+	assert!(doc.undo());
+	assert!(doc.undo());
+	assert_eq!((doc.map().width, doc.map().height), (4, 4));
+	assert_eq!(doc.map().entities[0].pos, Vec2(3, 3));
+}
+
+#[test]
+fn paste_rotated() {
+	let mut doc = EditorDocument::new(6, 6);
+	doc.set_terrain(Vec2(0, 0), core::Terrain::ForceE);
+	doc.place_entity(core::EntityArgs { kind: core::EntityKind::Bug, pos: Vec2(1, 0), face_dir: Some(core::Dir::Up) });
+	doc.copy(Vec2(0, 0), Vec2(1, 0));
+	doc.rotate_clipboard().unwrap();
+
+	assert!(doc.paste(Vec2(4, 2)));
+	assert_eq!(doc.map().get_terrain(Vec2(4, 2)), core::Terrain::ForceS);
+	let bug = doc.map().entities.last().unwrap();
+	assert_eq!(bug.pos, Vec2(4, 3));
+	assert_eq!(bug.face_dir, Some(core::Dir::Right));
+
+	// The paste is a single undo step
+	assert!(doc.undo());
+	assert_eq!(doc.map().get_terrain(Vec2(4, 2)), core::Terrain::Floor);
+	assert_eq!(doc.map().entities.len(), 1);
+}
+
+#[test]
+fn rotate_mirror_tables() {
+	assert_eq!(rotate_terrain(core::Terrain::ForceN), Some(core::Terrain::ForceE));
+	assert_eq!(rotate_terrain(core::Terrain::IceNW), Some(core::Terrain::IceNE));
+	assert_eq!(rotate_terrain(core::Terrain::PanelW), Some(core::Terrain::PanelN));
+	assert_eq!(mirror_terrain(core::Terrain::IceSE), Some(core::Terrain::IceSW));
+	assert_eq!(mirror_terrain(core::Terrain::ForceN), Some(core::Terrain::ForceN));
+	assert_eq!(mirror_terrain(core::Terrain::PanelE), Some(core::Terrain::PanelW));
+
+	// There is no south-west panel to turn the south-east panel into
+	assert_eq!(rotate_terrain(core::Terrain::PanelSE), None);
+	assert_eq!(mirror_terrain(core::Terrain::PanelSE), None);
+
+	for &terrain in core::ALL_TERRAIN.iter().filter(|&&terrain| terrain != core::Terrain::PanelSE) {
+		let rotate = |terrain| rotate_terrain(terrain).unwrap();
+		let mirror = |terrain| mirror_terrain(terrain).unwrap();
+		assert_eq!(rotate(rotate(rotate(rotate(terrain)))), terrain, "{:?}", terrain);
+		assert_eq!(mirror(mirror(terrain)), terrain, "{:?}", terrain);
+		// Mirroring reverses the direction of the rotation
+		assert_eq!(mirror(rotate(mirror(rotate(terrain)))), terrain, "{:?}", terrain);
+	}
+}
+
+#[test]
+fn clipboard_rotations() {
+	let mut doc = EditorDocument::new(6, 6);
+	doc.set_terrain(Vec2(0, 0), core::Terrain::IceNW);
+	doc.set_terrain(Vec2(2, 1), core::Terrain::ForceW);
+	doc.set_terrain(Vec2(1, 0), core::Terrain::BrownButton);
+	doc.set_terrain(Vec2(0, 1), core::Terrain::BearTrap);
+	doc.connect(Vec2(1, 0), Vec2(0, 1));
+	doc.place_entity(core::EntityArgs { kind: core::EntityKind::Bug, pos: Vec2(2, 0), face_dir: Some(core::Dir::Left) });
+	doc.copy(Vec2(0, 0), Vec2(2, 1));
+
+	let clip = doc.clipboard().unwrap().clone();
+	let describe = |clip: &Clipboard| format!("{:?} {:?} {:?} {:?}", clip.size, clip.terrain, clip.entities, clip.conns);
+	let mut rotated = clip.clone();
+	for _ in 0..4 {
+		rotated.rotate().unwrap();
+	}
+	assert_eq!(describe(&rotated), describe(&clip));
+
+	rotated.rotate().unwrap();
+	assert_eq!(rotated.size, Vec2(2, 3));
+	assert_eq!(rotated.get_terrain(Vec2(1, 0)), core::Terrain::IceNE);
+	assert_eq!(rotated.get_terrain(Vec2(0, 2)), core::Terrain::ForceN);
+	assert_eq!((rotated.entities[0].pos, rotated.entities[0].face_dir), (Vec2(1, 2), Some(core::Dir::Up)));
+	assert_eq!((rotated.conns[0].src, rotated.conns[0].dest), (Vec2(1, 1), Vec2(0, 0)));
+
+	let mut mirrored = clip.clone();
+	mirrored.mirror().unwrap();
+	assert_eq!(mirrored.get_terrain(Vec2(2, 0)), core::Terrain::IceNE);
+	assert_eq!((mirrored.entities[0].pos, mirrored.entities[0].face_dir), (Vec2(0, 0), Some(core::Dir::Right)));
+	mirrored.mirror().unwrap();
+	assert_eq!(describe(&mirrored), describe(&clip));
+}
+
+#[test]
+fn clipboard_refuses_south_east_panel() {
+	let mut doc = EditorDocument::new(4, 4);
+	doc.set_terrain(Vec2(1, 0), core::Terrain::PanelSE);
+	doc.set_terrain(Vec2(0, 0), core::Terrain::ForceN);
+	doc.copy(Vec2(0, 0), Vec2(1, 0));
+
+	assert!(doc.rotate_clipboard().is_err());
+	assert!(doc.mirror_clipboard().is_err());
+
+	// The clipboard is left unchanged
+	let clip = doc.clipboard().unwrap();
+	assert_eq!(clip.size, Vec2(2, 1));
+	assert_eq!(clip.terrain, [core::Terrain::ForceN, core::Terrain::PanelSE]);
+}
+
+#[test]
+fn save_round_trip() {
+	let mut doc = EditorDocument::new(3, 2);
+	doc.edit_properties(|props| {
+		props.name = String::from("Test");
+		props.time = 100;
+	});
+	doc.set_terrain(Vec2(2, 1), core::Terrain::Exit);
+	doc.place_entity(core::EntityArgs { kind: core::EntityKind::Player, pos: Vec2(0, 0), face_dir: None });
+	doc.place_entity(chip(Vec2(1, 1)));
+	doc.update_chips();
+
+	let loaded = EditorDocument::parse(&doc.save()).unwrap();
+	assert_eq!(loaded.properties(), doc.properties());
+	assert_eq!(loaded.properties().chips, 1);
+	assert_eq!(loaded.map().terrain, doc.map().terrain);
+	assert_eq!(loaded.map().entities.len(), 2);
+
+	let mut game = core::GameState::default();
+	game.load_dto(loaded.to_dto());
+	assert_eq!(game.field.get_terrain(Vec2(2, 1)), core::Terrain::Exit);
+	assert_eq!(game.ents.map.len(), 2);
+
+	let ld: dto::LevelDto = serde_json::from_str(&doc.save()).unwrap();
+	assert_eq!(ld.version, dto::LEVEL_VERSION);
+}
+
+#[test]
+fn resave_is_identical() {
+	for entry in std::fs::read_dir("data/levels").unwrap() {
+		let path = entry.unwrap().path();
+		let level = std::fs::read_to_string(&path).unwrap();
+		let ld = dto::LevelDto::parse(&level).unwrap();
+		let doc = EditorDocument::parse(&level).unwrap();
+		let saved = doc.save();
+		assert_eq!(saved, doc.save(), "{}", path.display());
+		assert_eq!(EditorDocument::parse(&saved).unwrap().save(), saved, "{}", path.display());
+
+		let resaved = dto::LevelDto::parse(&saved).unwrap();
+		assert_eq!(resaved.seed, ld.seed, "{}", path.display());
+		// Terrain missing from the legend is appended
+		assert!(resaved.map.legend.starts_with(&ld.map.legend), "{}", path.display());
+	}
+}
+
+#[test]
+fn reseed_is_undoable() {
+	let mut doc = EditorDocument::new(2, 2);
+	let seed = doc.properties().seed;
+	doc.reseed();
+	assert_ne!(doc.properties().seed, seed);
+	assert_eq!(doc.to_dto().seed, doc.properties().seed);
+	assert!(doc.undo());
+	assert_eq!(doc.properties().seed, seed);
+}
+
+#[test]
+fn connection_kinds() {
+	let mut doc = EditorDocument::new(4, 1);
+	doc.set_terrain(Vec2(0, 0), core::Terrain::RedButton);
+	doc.set_terrain(Vec2(1, 0), core::Terrain::CloneMachine);
+	doc.set_terrain(Vec2(2, 0), core::Terrain::BrownButton);
+	doc.connect(Vec2(0, 0), Vec2(1, 0));
+	doc.connect(Vec2(2, 0), Vec2(1, 0));
+	doc.connect(Vec2(3, 0), Vec2(1, 0));
+
+	let map = doc.map();
+	assert_eq!(map.conn_kind(&map.conns[0]), Ok(core::ConnKind::Clone));
+	assert!(map.conn_kind(&map.conns[1]).is_err());
+	assert!(map.conn_kind(&map.conns[2]).is_err());
+	assert_eq!(doc.invalid_connections(), [1, 2]);
+
+	assert!(doc.remove_connection(1));
+	assert_eq!(doc.invalid_connections(), [1]);
+	assert!(!doc.remove_connection(2));
+
+	doc.set_terrain(Vec2(3, 0), core::Terrain::Teleport);
+	doc.set_terrain(Vec2(1, 0), core::Terrain::Teleport);
+	assert_eq!(doc.map().conn_kind(&doc.map().conns[1]), Ok(core::ConnKind::Teleport));
+}
+
+/// Describes what the view shows independent of the handles of the entities and objects.
+fn view_summary(state: &VisualState) -> String {
+	let game = &state.game;
+	let mut ents = game.ents.map.values().map(|ent| format!("{:?} {:?} hidden={}", ent.kind, ent.pos, ent.hidden)).collect::<Vec<_>>();
+	ents.sort();
+	let mut objects = state.objects.map.values().map(|obj| format!("{:?} {:?} {:?} vis={}", obj.sprite, obj.model, obj.pos, obj.vis)).collect::<Vec<_>>();
+	objects.sort();
+	let player = game.ents.get(game.ps.entity).map(|ent| ent.pos);
+	format!("{} {} {:?} {:?}\nplayer: {:?}\n{:#?}\n{:#?}", game.field.name, game.field.chips, game.field.terrain, game.field.conns, player, ents, objects)
+}
+
+fn render(editor: &mut EditorGame) {
+	let input = EditorInput { screen_size: Vec2(800, 600), ..EditorInput::default() };
+	editor.render(&mut Recorder::default(), &input);
+}
+
+#[test]
+fn view_follows_edits() {
+	let level = fs::read_to_string("data/levels/level1.json").unwrap();
+	let mut editor = EditorGame::default();
+	editor.init(Resources::default());
+	editor.load_level(&level).unwrap();
+
+	// The edited view must match the view of the edited level loaded from scratch
+	let check = |editor: &EditorGame| {
+		let mut fresh = EditorGame::default();
+		fresh.init(Resources::default());
+		fresh.load_level(&editor.document().save()).unwrap();
+		assert_eq!(view_summary(editor.visual_state()), view_summary(fresh.visual_state()));
+	};
+
+	let doc = editor.document_mut();
+	doc.begin();
+	doc.apply_tool(Tool::Erase, Vec2(9, 13));
+	doc.apply_tool(Tool::Terrain(core::Terrain::Fire), Vec2(1, 1));
+	doc.apply_tool(Tool::Terrain(core::Terrain::ToggleWall), Vec2(2, 1));
+	doc.apply_tool(Tool::Terrain(core::Terrain::CloneMachine), Vec2(10, 6));
+	doc.apply_tool(Tool::Entity(core::EntityArgs { kind: core::EntityKind::Block, pos: Vec2::ZERO, face_dir: None }), Vec2(3, 8));
+	doc.apply_tool(Tool::Entity(core::EntityArgs { kind: core::EntityKind::Player, pos: Vec2::ZERO, face_dir: None }), Vec2(4, 4));
+	doc.connect(Vec2(1, 1), Vec2(2, 2));
+	doc.edit_properties(|props| props.name = String::from("Edited"));
+	doc.end();
+	render(&mut editor);
+	check(&editor);
+
+	// Terrain objects are replaced and removed objects are gone at once
+	editor.document_mut().apply_tool(Tool::Terrain(core::Terrain::Floor), Vec2(1, 1));
+	editor.document_mut().apply_tool(Tool::Erase, Vec2(4, 4));
+	render(&mut editor);
+	check(&editor);
+
+	assert!(editor.document_mut().undo());
+	assert!(editor.document_mut().undo());
+	render(&mut editor);
+	check(&editor);
+
+	assert!(editor.document_mut().undo());
+	render(&mut editor);
+	check(&editor);
+	assert_eq!(editor.visual_state().game.field.name, "Lesson 1");
+
+	assert!(editor.document_mut().redo());
+	assert!(editor.resize(1, 0, 0, 1));
+	render(&mut editor);
+	check(&editor);
+}
+
+#[test]
+fn playtest_restores_editing() {
+	let level = fs::read_to_string("data/levels/level1.json").unwrap();
+	let mut editor = EditorGame::default();
+	editor.init(Resources::default());
+	editor.load_level(&level).unwrap();
+	let original = editor.document().map().clone();
+	editor.document_mut().set_terrain(Vec2(1, 1), core::Terrain::Wall);
+	let saved = editor.document().save();
+
+	// A click over the palette starts a stroke without touching the map
+	let mut input = EditorInput { screen_size: Vec2(800, 600), left_click: true, dt: 0.1, ..EditorInput::default() };
+	editor.render(&mut Recorder::default(), &input);
+	assert_eq!(editor.document().save(), saved);
+
+	editor.toggle_playtest();
+	assert!(editor.is_playtesting());
+	for _ in 0..10 {
+		editor.render(&mut Recorder::default(), &input);
+	}
+	editor.toggle_playtest();
+	assert!(!editor.is_playtesting());
+	assert_eq!(editor.document().save(), saved);
+	assert_eq!(editor.visual_state().tiles, TileSet::default().edit);
+
+	// The stroke was closed when playtesting started, later edits are undone one by one
+	editor.document_mut().set_terrain(Vec2(2, 1), core::Terrain::Water);
+	editor.document_mut().set_terrain(Vec2(3, 1), core::Terrain::Water);
+	assert!(editor.document_mut().undo());
+	assert_eq!(editor.document().map().get_terrain(Vec2(2, 1)), core::Terrain::Water);
+	assert_eq!(editor.document().map().get_terrain(Vec2(3, 1)), original.get_terrain(Vec2(3, 1)));
+	assert!(editor.document_mut().undo());
+	assert_eq!(editor.document().save(), saved);
+
+	// Releasing the mouse does not close a group which is not open
+	input.left_click = false;
+	editor.render(&mut Recorder::default(), &input);
+	assert!(editor.document_mut().undo());
+	assert_eq!(editor.document().map().terrain, original.terrain);
+	assert!(!editor.document_mut().undo());
+}
+
+#[test]
+fn conn_list_overlay() {
+	let level = fs::read_to_string("data/levels/level1.json").unwrap();
+	let mut editor = EditorGame::default();
+	editor.init(Resources::default());
+	editor.load_level(&level).unwrap();
+	editor.document_mut().connect(Vec2(1, 1), Vec2(2, 2));
+	editor.document_mut().connect(Vec2(3, 3), Vec2(4, 4));
+	editor.next_conn();
+	assert_eq!(editor.conn_list(), [
+		"> 0: 1,1 -> 2,2 Invalid: Blank is not a button or teleport",
+		"  1: 3,3 -> 4,4 Invalid: Wall is not a button or teleport",
+	]);
+
+	let input = EditorInput { screen_size: Vec2(800, 600), ..EditorInput::default() };
+	let mut hidden = Recorder::default();
+	editor.render(&mut hidden, &input);
+
+	// The list is drawn on top as a panel and a text pass in screen space
+	editor.toggle_conn_list();
+	assert!(editor.is_conn_list_shown());
+	let mut shown = Recorder::default();
+	editor.render(&mut shown, &input);
+	assert_eq!(shown.passes.len(), hidden.passes.len() + 2);
+	let text = &shown.passes[shown.passes.len() - 1];
+	assert!(!text.pass.depth_test);
+	assert!(text.batches.iter().all(|batch| batch.vertices.iter().all(|v| v.pos.x <= 800.0 && v.pos.y >= 0.0)));
+	assert!(text.batches.iter().map(|batch| batch.prim_count()).sum::<usize>() > 0);
+
+	editor.toggle_conn_list();
+	let mut again = Recorder::default();
+	editor.render(&mut again, &input);
+	assert_eq!(again.passes.len(), hidden.passes.len());
+}
+
+#[test]
+fn type_properties() {
+	let level = fs::read_to_string("data/levels/level1.json").unwrap();
+	let mut editor = EditorGame::default();
+	editor.init(Resources::default());
+	editor.load_level(&level).unwrap();
+	assert!(!editor.type_char('x'));
+
+	// Typing is undone as a whole, backspace removes and control characters are ignored
+	editor.begin_text(TextField::Name);
+	for chr in "Lessno\u{8}\u{8}on 2\r".chars() {
+		assert!(editor.type_char(chr));
+	}
+	assert_eq!(editor.document().properties().name, "Lesson 1Lesson 2");
+	assert_eq!(editor.props_list()[0], "Name: Lesson 1Lesson 2_");
+	editor.end_text();
+	assert_eq!(editor.text_field(), None);
+	assert!(editor.document_mut().undo());
+	assert_eq!(editor.document().properties().name, "Lesson 1");
+
+	editor.begin_text(TextField::Hint);
+	editor.type_char('!');
+	editor.document_mut().edit_properties(|props| props.time = 0);
+	editor.end_text();
+	assert_eq!(editor.props_list(), [
+		"Name: Lesson 1",
+		"Hint: Collect chips to get past the chip socket. Use keys to open doors.!",
+		"Password: BDHP",
+		"Time: ---",
+		"Chips: 11 of 11",
+	]);
+
+	// The properties panel is drawn while typing or when shown
+	let input = EditorInput { screen_size: Vec2(800, 600), ..EditorInput::default() };
+	let mut hidden = Recorder::default();
+	editor.render(&mut hidden, &input);
+	editor.begin_text(TextField::Name);
+	let mut typing = Recorder::default();
+	editor.render(&mut typing, &input);
+	assert_eq!(typing.passes.len(), hidden.passes.len() + 2);
+
+	// Clicking finishes typing
+	editor.render(&mut Recorder::default(), &EditorInput { left_click: true, ..input.clone() });
+	assert_eq!(editor.text_field(), None);
+	editor.toggle_props();
+	assert!(editor.is_props_shown());
+	let mut shown = Recorder::default();
+	editor.render(&mut shown, &input);
+	assert_eq!(shown.passes.len(), hidden.passes.len() + 2);
+}
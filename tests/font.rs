@@ -0,0 +1,41 @@
+use chipgame::visual::{FontAtlas, TextAlign, TextStyle};
+
+#[test]
+fn wrap_lines() {
+	let font = FontAtlas::default();
+	let width = i32::max(font.measure("hello"), font.measure("world"));
+
+	assert_eq!(font.wrap("hello world", None), ["hello world"]);
+	assert_eq!(font.wrap("hello world", Some(width)), ["hello", "world"]);
+	assert_eq!(font.wrap("hello\n\nworld", Some(1000)), ["hello", "", "world"]);
+
+	// Words wider than a line are broken between characters
+	let lines = font.wrap("hellohello", Some(width));
+	assert_eq!(lines, ["hello", "hello"]);
+}
+
+#[test]
+fn layout_align() {
+	let font = FontAtlas::default();
+	let style = TextStyle { scale: 2.0, align: TextAlign::Center, ..TextStyle::default() };
+	let layout = font.layout("Chip\nChallenge", &style);
+
+	let chip = font.measure("Chip") as f32 * 2.0;
+	let challenge = font.measure("Challenge") as f32 * 2.0;
+	assert_eq!(layout.size.x, challenge);
+	assert_eq!(layout.size.y, font.line_height as f32 * 4.0);
+
+	// The short line is centered in the block and the second line starts one line lower
+	let first = layout.quads[0];
+	assert_eq!(first.pos.x, f32::floor((challenge - chip) * 0.25) * 2.0);
+	assert_eq!(first.pos.y, 0.0);
+	assert_eq!(layout.quads[4].pos, cvmath::Vec2(0.0, font.line_height as f32 * 2.0));
+	assert_eq!(layout.quads.len(), "ChipChallenge".len());
+}
+
+#[test]
+fn missing_glyphs() {
+	let font = FontAtlas::default();
+	assert_eq!(font.glyph('\u{2603}'), &font.fallback);
+	assert_eq!(font.measure("\u{2603}"), font.fallback.advance);
+}
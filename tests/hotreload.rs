@@ -0,0 +1,96 @@
+use std::{env, fs, time};
+use chipgame::visual::{FontAtlas, Resources, Skin, Sprite, SpriteAtlas, WatchedFile};
+use cvmath::Vec2;
+
+const DATA: &str = include_str!("../data/data.json");
+
+#[test]
+fn watched_file_changes() {
+	let path = env::temp_dir().join(format!("chipgame-watch-{}.txt", std::process::id()));
+	let _ = fs::remove_file(&path);
+
+	// Missing files are never reported as changed
+	let mut file = WatchedFile::new(path.clone());
+	assert!(!file.poll());
+
+	fs::write(&path, "a").unwrap();
+	assert!(file.poll());
+	assert!(!file.poll());
+
+	// Touching the file again is reported once
+	let later = time::SystemTime::now() + time::Duration::from_secs(10);
+	fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+	assert!(file.poll());
+	assert!(!file.poll());
+
+	fs::remove_file(&path).unwrap();
+	assert!(!file.poll());
+	assert!(!WatchedFile::new(path).poll());
+}
+
+fn resources() -> Resources {
+	Resources { tileset_size: Vec2(1024, 1024), font_size: Vec2(1024, 1024), ..Resources::default() }
+}
+
+#[test]
+fn reload_data() {
+	let mut resources = resources();
+	assert_eq!(resources.reload_data(DATA), Ok(None));
+
+	// The atlas is updated in place when its texture stays the same
+	let moved = DATA.replacen(r#""Wall": [0, 1]"#, r#""Wall": [1, 1]"#, 1);
+	assert_eq!(resources.reload_data(&moved), Ok(None));
+	assert_eq!(resources.atlas.get(Sprite::Wall).index, Vec2(1, 1));
+	assert_eq!(resources.reload_data(DATA), Ok(None));
+	assert_eq!(resources.atlas, SpriteAtlas::default());
+}
+
+#[test]
+fn reload_bad_data() {
+	let mut resources = resources();
+	let atlas = resources.atlas.clone();
+	let font = resources.font_atlas.clone();
+	let check = |resources: &Resources| {
+		assert_eq!(resources.atlas, atlas);
+		assert_eq!(resources.font_atlas, font);
+	};
+
+	// Errors are reported and the old atlas and font are kept
+	assert!(resources.reload_data("{").is_err());
+	check(&resources);
+
+	let bad_anim = DATA.replacen(r#""frame_time": 0.125"#, r#""frame_time": 0.0"#, 1);
+	assert!(resources.reload_data(&bad_anim).is_err());
+	check(&resources);
+
+	// Sprites and glyphs outside of their textures reject the whole file
+	let outside = DATA.replacen(r#""Wall": [0, 1]"#, r#""Wall": [100, 1]"#, 1);
+	let err = resources.reload_data(&outside).unwrap_err();
+	assert!(err.starts_with("Sprite Wall is outside of tileset"), "{}", err);
+	check(&resources);
+
+	let outside = DATA.replacen(r#""!": { "rect": [8, 0, 1, 9]"#, r#""!": { "rect": [8000, 0, 1, 9]"#, 1);
+	assert!(resources.reload_data(&outside).is_err());
+	check(&resources);
+}
+
+#[test]
+fn reload_updates_skin() {
+	let mut resources = resources();
+	let atlas = SpriteAtlas::default();
+	let mut other = atlas.clone();
+	other.name = String::from("Other");
+	resources.add_skin(Skin::new(atlas.clone(), Default::default(), Vec2(1024, 1024)).unwrap());
+	resources.add_skin(Skin::new(other.clone(), Default::default(), Vec2(1024, 1024)).unwrap());
+	assert_eq!(resources.atlas, atlas);
+
+	// Reloading changes the current skin so switching skins does not bring back the old atlas
+	let moved = DATA.replacen(r#""Wall": [0, 1]"#, r#""Wall": [1, 1]"#, 1);
+	assert_eq!(resources.reload_data(&moved), Ok(None));
+	resources.next_skin();
+	assert_eq!(resources.atlas, other);
+	resources.next_skin();
+	assert_eq!(resources.atlas.get(Sprite::Wall).index, Vec2(1, 1));
+	assert_eq!(resources.skins[0].atlas, resources.atlas);
+	assert_eq!(resources.font_atlas, FontAtlas::default());
+}
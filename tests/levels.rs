@@ -0,0 +1,114 @@
+use std::fs;
+use chipgame::core::{dto, leveltext};
+
+fn for_each_level(mut f: impl FnMut(&str, dto::LevelDto)) {
+	for entry in fs::read_dir("data/levels").unwrap() {
+		let path = entry.unwrap().path();
+		if path.extension().map_or(false, |ext| ext == "json") {
+			let json = fs::read_to_string(&path).unwrap();
+			let ld = dto::LevelDto::parse(&json).unwrap_or_else(|err| panic!("{}: {}", path.display(), err));
+			f(&path.display().to_string(), ld);
+		}
+	}
+}
+
+fn to_value(ld: &dto::LevelDto) -> serde_json::Value {
+	let mut ld = ld.clone();
+	ld.map.encoding = dto::MapEncoding::Plain;
+	serde_json::to_value(&ld).unwrap()
+}
+
+#[test]
+fn levels_are_migrated() {
+	for_each_level(|path, ld| {
+		assert_eq!(ld.version, dto::LEVEL_VERSION, "{}", path);
+	});
+}
+
+#[test]
+fn map_encodings_round_trip() {
+	for_each_level(|path, ld| {
+		for encoding in [dto::MapEncoding::Plain, dto::MapEncoding::Rle, dto::MapEncoding::Base64] {
+			let mut encoded = ld.clone();
+			encoded.map.encoding = encoding;
+			let json = serde_json::to_string(&encoded).unwrap();
+			let decoded = dto::LevelDto::parse(&json).unwrap();
+			assert_eq!(decoded.map.encoding, encoding, "{}", path);
+			assert_eq!(to_value(&decoded), to_value(&ld), "{} {:?}", path, encoding);
+		}
+	});
+}
+
+#[test]
+fn rle_runs_are_bounded() {
+	let level = |data: &str| format!(r#"{{
+		"version": 1, "name": "", "hint": "", "password": "", "seed": 0, "time": 0, "chips": 0,
+		"map": {{ "width": 2, "height": 2, "data": "{}", "legend": ["Floor", "Wall"] }},
+		"entities": [], "connections": []
+	}}"#, data);
+
+	let ld = dto::LevelDto::parse(&level("rle:3*0,1")).unwrap();
+	assert_eq!(ld.map.data, [0, 0, 0, 1]);
+
+	// Runs past the size of the map fail before they are expanded
+	assert!(dto::LevelDto::parse(&level("rle:4*0,1")).is_err());
+	assert!(dto::LevelDto::parse(&level("rle:18446744073709551615*0")).is_err());
+}
+
+#[test]
+fn text_format_round_trip() {
+	for_each_level(|path, ld| {
+		let text = leveltext::format(&ld).unwrap();
+		let parsed = dto::LevelDto::parse(&text).unwrap();
+
+		assert_eq!(to_value(&parsed), to_value(&ld), "{}", path);
+
+		assert_eq!(leveltext::format(&parsed).unwrap(), text, "{}", path);
+	});
+}
+
+#[test]
+fn text_format_keeps_entity_order() {
+	let text = "\
+size: 3x2
+
+[legend]
+. Floor
+
+[entities]
+@v. c
+. # .
+
+[entities]
+. . B>
+. . .
+";
+	let ld = leveltext::parse(text).unwrap();
+	let kinds = |ld: &dto::LevelDto| ld.entities.iter().map(|ent| format!("{:?}", ent.kind)).collect::<Vec<_>>();
+	assert_eq!(kinds(&ld), ["Player", "Chip", "Block", "Bug"]);
+	assert!(!leveltext::format(&ld).unwrap().contains("[order]"));
+
+	// Entities created out of reading order keep their order
+	let mut reordered = ld.clone();
+	reordered.entities = vec![ld.entities[3], ld.entities[2], ld.entities[0], ld.entities[1]];
+	let text = leveltext::format(&reordered).unwrap();
+	assert!(text.ends_with("\n[order]\n1 2 0 3\n"), "{}", text);
+	let parsed = leveltext::parse(&text).unwrap();
+	assert_eq!(kinds(&parsed), ["Bug", "Block", "Player", "Chip"]);
+	assert_eq!(to_value(&parsed), to_value(&reordered));
+
+	// Every entity must be listed exactly once
+	for order in ["1 2 0", "1 2 0 0", "1 2 0 4", "1 2 0 x"] {
+		let text = text.replace("1 2 0 3", order);
+		assert!(leveltext::parse(&text).is_err(), "{}", order);
+	}
+}
+
+#[test]
+fn levels_load() {
+	for_each_level(|_, ld| {
+		let mut game = chipgame::core::GameState::default();
+		game.load(&serde_json::to_string(&ld).unwrap());
+		game.load(&leveltext::format(&ld).unwrap());
+	});
+}
@@ -0,0 +1,88 @@
+use chipgame::visual::{EntityMesh, MeshDef, MeshModel};
+use cvmath::Vec3;
+
+/// Writes the triangles as a binary STL file with zeroed normals.
+fn binary_stl(triangles: &[[[f32; 3]; 3]]) -> Vec<u8> {
+	let mut data = vec![0u8; 80];
+	data.extend_from_slice(&(triangles.len() as u32).to_le_bytes());
+	for triangle in triangles {
+		data.extend_from_slice(&[0u8; 12]);
+		for corner in triangle {
+			for coord in corner {
+				data.extend_from_slice(&coord.to_le_bytes());
+			}
+		}
+		data.extend_from_slice(&[0u8; 2]);
+	}
+	data
+}
+
+#[test]
+fn parse_binary() {
+	let data = binary_stl(&[
+		[[0.0, 0.0, 2.0], [4.0, 0.0, 2.0], [4.0, 6.0, 2.0]],
+		[[0.0, 0.0, 2.0], [4.0, 6.0, 2.0], [0.0, 6.0, 5.0]],
+	]);
+	let mesh = MeshModel::parse_stl(&data, 2.0).unwrap();
+	assert_eq!(mesh.indices, [0, 1, 2, 3, 4, 5]);
+
+	// Centered on the tile with the bottom at zero and scaled
+	assert_eq!(mesh.positions[0], Vec3(-4.0, -6.0, 0.0));
+	assert_eq!(mesh.positions[2], Vec3(4.0, 6.0, 0.0));
+	assert_eq!(mesh.positions[5], Vec3(-4.0, 6.0, 6.0));
+
+	// Normals follow the counter-clockwise winding
+	assert_eq!(mesh.normals[0], Vec3(0.0, 0.0, 1.0));
+	assert_eq!(mesh.uvs[2], cvmath::Vec2(1.0, 1.0));
+}
+
+#[test]
+fn parse_ascii() {
+	let text = "solid test
+  facet normal 0 0 0
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 0 1 0
+    endloop
+  endfacet
+endsolid test
+";
+	let mesh = MeshModel::parse_stl(text.as_bytes(), 1.0).unwrap();
+	assert_eq!(mesh.positions.len(), 3);
+	assert_eq!(mesh.normals[1], Vec3(0.0, 0.0, 1.0));
+
+	assert!(MeshModel::parse_stl(b"solid empty\nendsolid empty\n", 1.0).is_err());
+	assert!(MeshModel::parse_stl(b"solid bad\nvertex 0 0\n", 1.0).is_err());
+	assert!(MeshModel::parse_stl(&[1, 2, 3], 1.0).is_err());
+}
+
+#[test]
+fn degenerate_triangles() {
+	let data = binary_stl(&[
+		[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [2.0, 0.0, 0.0]],
+		[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+		[[5.0, 5.0, 5.0], [5.0, 5.0, 5.0], [5.0, 5.0, 5.0]],
+	]);
+	// Triangles without area are skipped and do not count towards the bounds
+	let mesh = MeshModel::parse_stl(&data, 1.0).unwrap();
+	assert_eq!(mesh.positions.len(), 3);
+	assert_eq!(mesh.positions[0], Vec3(-0.5, -0.5, 0.0));
+	assert!(mesh.normals.iter().all(|&normal| normal == Vec3(0.0, 0.0, 1.0)));
+
+	let data = binary_stl(&[[[0.0, 0.0, 0.0], [1.0, 1.0, 1.0], [2.0, 2.0, 2.0]]]);
+	assert!(MeshModel::parse_stl(&data, 1.0).is_err());
+}
+
+#[test]
+fn builtin_models() {
+	let meshes = EntityMesh::load_all("data", include_str!("../data/data.json")).unwrap();
+	assert_eq!(meshes.len(), MeshDef::parse_all(include_str!("../data/data.json")).unwrap().len());
+	for mesh in &meshes {
+		assert!(!mesh.model.indices.is_empty(), "{:?}", mesh.def.kind);
+		assert!(mesh.model.normals.iter().all(|normal| normal.x.is_finite() && normal.y.is_finite() && normal.z.is_finite()), "{:?}", mesh.def.kind);
+	}
+
+	let err = EntityMesh::load_all("missing", include_str!("../data/data.json")).unwrap_err();
+	assert!(err.starts_with("missing/"), "{}", err);
+}
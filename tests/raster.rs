@@ -0,0 +1,94 @@
+use chipgame::core;
+use chipgame::raster::{render_level, Image, RenderOptions};
+use chipgame::visual::{Sprite, SpriteAtlas, TileSet};
+use cvmath::Vec2;
+
+const LEVEL: &str = r#"{
+	"name": "Raster", "hint": "", "password": "", "seed": 1, "time": 0, "chips": 1,
+	"map": { "width": 3, "height": 2, "data": [1, 2, 3, 1, 1, 1], "legend": ["Blank", "Floor", "Wall", "Fire"] },
+	"entities": [{ "kind": "Chip", "pos": [1, 1] }],
+	"connections": [{ "src": [0, 1], "dest": [2, 1] }]
+}"#;
+
+/// Tileset where every tile is filled with a color unique to its cell.
+fn tileset(atlas: &SpriteAtlas) -> Image {
+	let mut image = Image::new(atlas.tile_size * 16, atlas.tile_size * 32, [0, 0, 0, 0]);
+	for y in 0..image.height {
+		for x in 0..image.width {
+			image.pixels[(y * image.width + x) as usize] = cell_color(Vec2(x, y) / atlas.tile_size);
+		}
+	}
+	image
+}
+
+fn cell_color(cell: Vec2<i32>) -> [u8; 4] {
+	[cell.x as u8 * 8 + 4, cell.y as u8 * 4 + 4, 200, 255]
+}
+
+fn render(opts: &RenderOptions) -> (Image, SpriteAtlas) {
+	let data = include_str!("../data/data.json");
+	let atlas = SpriteAtlas::default();
+	let mut game = core::GameState::default();
+	game.field.defs = core::TerrainDefs::parse(data).unwrap();
+	game.load(LEVEL);
+	let image = render_level(&game, &tileset(&atlas), &atlas, &TileSet::default().edit, opts);
+	(image, atlas)
+}
+
+#[test]
+fn render_tiles() {
+	let (image, atlas) = render(&RenderOptions::default());
+	let size = atlas.tile_size;
+	assert_eq!((image.width, image.height), (size * 3, size * 2));
+
+	let color = |sprite| cell_color(atlas.get(sprite).index);
+	let center = |x: i32, y: i32| image.get(x * size + size / 2, y * size + size / 2);
+	assert_eq!(center(0, 0), color(Sprite::Floor));
+	assert_eq!(center(1, 0), color(Sprite::Wall));
+	// Fire is drawn on top of its terrain and the chip on top of the floor
+	assert_eq!(center(2, 0), color(Sprite::Fire));
+	assert_eq!(center(1, 1), color(Sprite::Chip));
+	assert_eq!(image.get(0, 0), color(Sprite::Floor));
+
+	// Scaling changes the size of the tiles
+	let (small, _) = render(&RenderOptions { scale: 0.5, ..RenderOptions::default() });
+	assert_eq!((small.width, small.height), (size / 2 * 3, size / 2 * 2));
+	assert_eq!(small.get(size / 2 + size / 4, size / 4), color(Sprite::Wall));
+
+	// Images survive a round trip through PNG
+	let mut png = Vec::new();
+	image.encode_png(&mut png).unwrap();
+	assert_eq!(Image::decode_png(&png[..]).unwrap(), image);
+}
+
+#[test]
+fn render_grid() {
+	let (image, atlas) = render(&RenderOptions { grid: true, ..RenderOptions::default() });
+	let size = atlas.tile_size;
+	let floor = cell_color(atlas.get(Sprite::Floor).index);
+	let darker = [0, 1, 2].map(|i| (floor[i] as u32 * 191 / 255) as u8);
+
+	// Lines between the tiles are darkened, the tiles are not
+	assert_eq!(image.get(0, 5), [darker[0], darker[1], darker[2], 255]);
+	assert_eq!(image.get(5, size)[..3], darker[..]);
+	assert_eq!(image.get(5, 5), floor);
+}
+
+#[test]
+fn render_connections() {
+	let (plain, atlas) = render(&RenderOptions::default());
+	let (image, _) = render(&RenderOptions { connections: true, ..RenderOptions::default() });
+	let size = atlas.tile_size;
+	let blue = [0, 0, 255, 255];
+
+	// The arrow runs between the tile centers with its head at the destination
+	let y = size + size / 2;
+	assert_eq!(image.get(size / 2, y), blue);
+	assert_eq!(image.get(size + size / 2, y), blue);
+	assert_eq!(image.get(2 * size + size / 2, y), blue);
+	assert_eq!(image.get(2 * size + size / 2 - 12, y - 6), blue);
+	assert_eq!(image.get(2 * size + size / 2 - 12, y + 6), blue);
+	assert_ne!(plain.get(size + size / 2, y), blue);
+	// Nothing else is drawn over
+	assert_eq!(image.get(size / 2, size / 2), plain.get(size / 2, size / 2));
+}
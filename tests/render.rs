@@ -0,0 +1,104 @@
+use std::{env, fs};
+use chipgame::visual::{Blend, PrimKind, Recorder, Skin, Sprite, VisualState};
+
+const SCREEN_SIZE: cvmath::Vec2<i32> = cvmath::Vec2 { x: 800, y: 600 };
+
+fn load_level(name: &str, hud: bool) -> VisualState {
+	let mut state = VisualState::default();
+	state.init();
+	state.load_data(include_str!("../data/data.json")).unwrap();
+	state.resources.screen_size = SCREEN_SIZE;
+	state.load_level(&fs::read_to_string(format!("data/levels/{}.json", name)).unwrap());
+	state.hud.hidden = !hud;
+	state.dt = 1.0;
+	state
+}
+
+fn record(state: &mut VisualState) -> Recorder {
+	let mut recorder = Recorder::default();
+	state.draw(&mut recorder);
+	recorder
+}
+
+/// Records one frame of the level with the camera settled on the player.
+fn record_level(name: &str, hud: bool) -> Recorder {
+	record(&mut load_level(name, hud))
+}
+
+/// Compares the summary against the golden file, run with `CHIPGAME_BLESS=1` to update the golden files.
+fn check_golden(name: &str, summary: &str) {
+	let path = format!("tests/golden/{}.txt", name);
+	if env::var_os("CHIPGAME_BLESS").is_some() {
+		fs::create_dir_all("tests/golden").unwrap();
+		fs::write(&path, summary).unwrap();
+		return;
+	}
+	let golden = fs::read_to_string(&path).unwrap_or_else(|err| panic!("{}: {}, run with CHIPGAME_BLESS=1 to create it:\n{}", path, err, summary));
+	assert!(golden == summary, "{} differs from the rendered frame:\n{}", path, summary);
+}
+
+#[test]
+fn frame_passes() {
+	let recorder = record_level("level1", true);
+	assert_eq!(recorder.frames, 1);
+	assert_eq!(recorder.clears.len(), 1);
+	assert!(recorder.clears[0].is_some());
+
+	// The field is depth tested, followed by the HUD panels and its text
+	assert_eq!(recorder.passes.len(), 3);
+	let field = &recorder.passes[0];
+	assert!(field.pass.depth_test);
+	assert_eq!(field.batches[0].kind, PrimKind::Triangles);
+	assert_eq!(field.batches[0].blend, Blend::Solid);
+	assert!(field.batches.iter().any(|batch| batch.blend == Blend::Alpha));
+	for hud in &recorder.passes[1..] {
+		assert!(!hud.pass.depth_test);
+		assert_eq!(hud.pass.blend, Blend::Alpha);
+		assert!(!hud.batches.is_empty());
+	}
+
+	let recorder = record_level("level1", false);
+	assert_eq!(recorder.passes.len(), 1);
+}
+
+#[test]
+fn golden_frames() {
+	for name in ["level1", "level2", "level3"] {
+		check_golden(name, &record_level(name, true).summary());
+	}
+}
+
+#[test]
+fn terrain_follows_atlas() {
+	let mut state = load_level("level1", false);
+	let terrain = |recorder: &Recorder| format!("{:?}", recorder.passes[0].batches[0].vertices);
+	let before = terrain(&record(&mut state));
+
+	// Editing the atlas in place rebuilds the cached terrain, walls are not animated so they are only drawn from the cache
+	state.resources.atlas.sprites[Sprite::Wall as usize].index.x += 1;
+	assert_ne!(terrain(&record(&mut state)), before);
+
+	state.resources.atlas.sprites[Sprite::Wall as usize].index.x -= 1;
+	assert_eq!(terrain(&record(&mut state)), before);
+}
+
+#[test]
+fn switch_skins() {
+	let mut state = load_level("level1", false);
+	let atlas = state.resources.atlas.clone();
+	let mut other = atlas.clone();
+	other.name = String::from("Other");
+	other.sprites[Sprite::Wall as usize].index.x += 1;
+	let size = cvmath::Vec2(1024, 1024);
+	state.resources.add_skin(Skin::new(atlas, Default::default(), size).unwrap());
+	state.resources.add_skin(Skin::new(other.clone(), Default::default(), size).unwrap());
+	let terrain = |recorder: &Recorder| format!("{:?}", recorder.passes[0].batches[0].vertices);
+	let first = terrain(&record(&mut state));
+
+	// Switching skins changes the atlas the terrain is drawn with and wraps around
+	state.resources.next_skin();
+	assert_eq!(state.resources.atlas, other);
+	assert_ne!(terrain(&record(&mut state)), first);
+	state.resources.next_skin();
+	assert_eq!(terrain(&record(&mut state)), first);
+}
@@ -0,0 +1,95 @@
+use chipgame::visual::{Sprite, SpriteAtlas};
+use cvmath::Vec2;
+
+const DATA: &str = include_str!("../data/data.json");
+
+#[test]
+fn sequence_frames() {
+	let atlas = SpriteAtlas::default();
+	let uv = |sprite, time| atlas.frame(sprite, time).uv;
+	let stride = atlas.tile_size + atlas.gutter * 2;
+	let cell = |sprite| atlas.get(sprite).index.map(|c| (c * stride + atlas.gutter) as f32);
+
+	// The exit cycles through its sequence and wraps around
+	assert_eq!(uv(Sprite::Exit1, 0.0), cell(Sprite::Exit3));
+	assert_eq!(uv(Sprite::Exit1, 0.2), cell(Sprite::Exit2));
+	assert_eq!(uv(Sprite::Exit1, 0.3), cell(Sprite::Exit1));
+	assert_eq!(uv(Sprite::Exit1, 0.4), cell(Sprite::Exit3));
+	// Negative times show the first frame
+	assert_eq!(uv(Sprite::Exit1, -1.0), cell(Sprite::Exit3));
+}
+
+#[test]
+fn definition_frames() {
+	let mut atlas = SpriteAtlas::default();
+	let first = atlas.frame(Sprite::Wall, 0.0);
+	assert!(!atlas.is_animated(Sprite::Wall));
+
+	// Frames are laid out below the first frame
+	let def = &mut atlas.sprites[Sprite::Wall as usize];
+	def.frames = 3;
+	def.frame_time = 0.5;
+	assert!(atlas.is_animated(Sprite::Wall));
+	let stride = (atlas.tile_size + atlas.gutter * 2) as f32;
+	assert_eq!(atlas.frame(Sprite::Wall, 0.2).uv, first.uv);
+	assert_eq!(atlas.frame(Sprite::Wall, 1.2).uv, first.uv + Vec2(0.0, stride * 2.0));
+	assert_eq!(atlas.frame(Sprite::Wall, 1.6).uv, first.uv);
+	// Negative times show the first frame
+	assert_eq!(atlas.frame(Sprite::Wall, -1.2).uv, first.uv);
+}
+
+#[test]
+fn mirror_alternates() {
+	let atlas = SpriteAtlas::default();
+	let plain = atlas.frame(Sprite::Fire, 0.1);
+	assert!(plain.uv_size.x > 0.0);
+
+	// Every other frame flips the texture rect around its right edge
+	let mirrored = atlas.frame(Sprite::Fire, 0.2);
+	assert_eq!(mirrored.uv, plain.uv + Vec2(plain.uv_size.x, 0.0));
+	assert_eq!(mirrored.uv_size, Vec2(-plain.uv_size.x, plain.uv_size.y));
+	assert_eq!(atlas.frame(Sprite::Fire, 0.35), plain);
+}
+
+#[test]
+fn scroll_offsets() {
+	let atlas = SpriteAtlas::default();
+	assert_eq!(atlas.frame(Sprite::Wall, 1.5).scroll, Vec2(0.0, 0.0));
+
+	// The texture moves against the scroll velocity and wraps within the tile
+	assert_eq!(atlas.frame(Sprite::ForceRight, 0.25).scroll, Vec2(0.75, 0.0));
+	assert_eq!(atlas.frame(Sprite::ForceUp, 0.25).scroll, Vec2(0.0, 0.25));
+	assert_eq!(atlas.frame(Sprite::ForceRight, 1.25).scroll, Vec2(0.75, 0.0));
+	assert_eq!(atlas.frame(Sprite::Water, 2.5).scroll, Vec2(0.75, 0.875));
+	// Scrolling does not change the texture rect
+	assert_eq!(atlas.frame(Sprite::Water, 2.5).uv, atlas.frame(Sprite::Water, 0.0).uv);
+}
+
+#[test]
+fn animated_sprites() {
+	let atlas = SpriteAtlas::default();
+	assert!(atlas.is_animated(Sprite::Exit1));
+	assert!(atlas.is_animated(Sprite::Fire));
+	assert!(atlas.is_animated(Sprite::Water));
+	assert!(atlas.is_animated(Sprite::ForceLeft));
+	assert!(!atlas.is_animated(Sprite::Exit2));
+	assert!(!atlas.is_animated(Sprite::Floor));
+	assert!(!atlas.anim(Sprite::Floor).is_animated());
+}
+
+#[test]
+fn zero_frame_time() {
+	assert!(SpriteAtlas::parse_all(DATA).is_ok());
+
+	// Sequences and multi frame sprites need a frame time
+	let sequence = DATA.replace(r#""frame_time": 0.125"#, r#""frame_time": 0.0"#);
+	assert_ne!(sequence, DATA);
+	assert_eq!(SpriteAtlas::parse_all(&sequence).unwrap_err(), "Invalid animation for sprite Exit1 in tileset Color");
+
+	let frames = DATA.replace(r#""Wall": [0, 1]"#, r#""Wall": { "index": [0, 1], "frames": 2 }"#);
+	assert_ne!(frames, DATA);
+	assert_eq!(SpriteAtlas::parse_all(&frames).unwrap_err(), "Invalid animation for sprite Wall in tileset Color");
+
+	let negative = DATA.replace(r#""frame_time": 0.125"#, r#""frame_time": -1.0"#);
+	assert!(SpriteAtlas::parse_all(&negative).is_err());
+}
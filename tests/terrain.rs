@@ -0,0 +1,47 @@
+use chipgame::core::TerrainDefs;
+use chipgame::visual::TileSet;
+
+const DATA: &str = include_str!("../data/data.json");
+const HINT: &str = r#"		{ "terrain": "Hint", "solid": 0, "play": { "sprite": "Hint", "model": "Floor" }, "edit": { "sprite": "Hint", "model": "Floor" } },
+"#;
+
+fn edit(from: &str, to: &str) -> String {
+	let json = DATA.replacen(from, to, 1);
+	assert_ne!(json, DATA, "{} not found in the data file", from);
+	json
+}
+
+#[test]
+fn builtin_data() {
+	assert!(TerrainDefs::parse(DATA).is_ok());
+	assert!(TileSet::parse(DATA).is_ok());
+}
+
+#[test]
+fn missing_terrain() {
+	let json = edit(HINT, "");
+	assert_eq!(TerrainDefs::parse(&json).unwrap_err(), "Missing terrain definition: Hint");
+	assert_eq!(TileSet::parse(&json).unwrap_err(), "Missing terrain definition: Hint");
+}
+
+#[test]
+fn duplicate_terrain() {
+	let json = edit(HINT, &format!("{}{}", HINT, HINT));
+	assert_eq!(TerrainDefs::parse(&json).unwrap_err(), "Duplicate terrain definition: Hint");
+	assert_eq!(TileSet::parse(&json).unwrap_err(), "Duplicate terrain definition: Hint");
+}
+
+#[test]
+fn solid_flags() {
+	let json = edit(r#""terrain": "Wall", "solid": 15"#, r#""terrain": "Wall", "solid": 16"#);
+	assert_eq!(TerrainDefs::parse(&json).unwrap_err(), "Invalid solid flags for Wall: 16");
+}
+
+#[test]
+fn unsupported_model() {
+	let json = edit(r#""play": { "sprite": "Wall", "model": "Wall" }"#, r#""play": { "sprite": "Wall", "model": "WallV2" }"#);
+	assert_eq!(TileSet::parse(&json).unwrap_err(), "Unsupported model for Wall: WallV2");
+
+	let json = edit(r#""edit": { "sprite": "Hint", "model": "Floor" }"#, r#""edit": { "sprite": "Hint", "model": "SpriteShadow" }"#);
+	assert_eq!(TileSet::parse(&json).unwrap_err(), "Unsupported model for Hint: SpriteShadow");
+}